@@ -0,0 +1,268 @@
+//! A module for the static walls of the world, such as obstacles and the shape of the arena.
+
+use ggez::{
+    graphics,
+    graphics::{Color, DrawMode, DrawParam, FilterMode, Image, Mesh, MeshBuilder},
+    nalgebra::{Point2, Vector2},
+    Context, GameError, GameResult,
+};
+use serde::{Deserialize, Serialize};
+
+/// The color that walls are drawn with
+const WALL_COLOR: [f32; 4] = [0.05, 0.1, 0.15, 1.0];
+
+/// The configuration structure specifically for the arena that is read and deserialized from
+/// `config.ron`.
+/// Any field that is left out takes its value from `ArenaConfig::default()`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ArenaConfig {
+    /// The outline of the arena as a polygon of (x, y) points.
+    /// Everything outside of the polygon is a wall. If not provided, the whole world is open.
+    pub shape: Option<Vec<(f32, f32)>>,
+    /// The path of a PNG image within the assets folder that is stretched over the world, where
+    /// black or transparent pixels are walls
+    pub mask: Option<String>,
+    /// The static obstacles within the arena
+    pub obstacles: Vec<Obstacle>,
+    /// The distance from a wall at which fish begin to steer away from it
+    pub wall_avoidance_distance: f32,
+    /// Whether or not walls block fish from perceiving what is behind them
+    pub walls_block_sight: bool,
+}
+
+impl Default for ArenaConfig {
+    /// An arena without any walls
+    fn default() -> Self {
+        Self {
+            shape: None,
+            mask: None,
+            obstacles: Vec::new(),
+            wall_avoidance_distance: 30.0,
+            walls_block_sight: true,
+        }
+    }
+}
+
+/// A static obstacle that fish cannot swim through and food cannot spawn within
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum Obstacle {
+    /// A circle around the (x, y) center with the provided radius
+    Circle { center: (f32, f32), radius: f32 },
+    /// A polygon with the provided (x, y) points
+    Polygon { points: Vec<(f32, f32)> },
+}
+
+impl Obstacle {
+    /// Returns whether or not the provided point is within the obstacle
+    pub fn contains(&self, point: Point2<f32>) -> bool {
+        match self {
+            Obstacle::Circle { center, radius } => {
+                (point - Point2::new(center.0, center.1)).magnitude() <= *radius
+            }
+            Obstacle::Polygon { points } => polygon_contains(points, point),
+        }
+    }
+}
+
+/// A grid of walls read from an image
+#[derive(Clone)]
+pub struct Mask {
+    /// The width of the mask in pixels
+    width: usize,
+    /// The height of the mask in pixels
+    height: usize,
+    /// Whether or not each pixel is a wall, stored row by row
+    walls: Vec<bool>,
+}
+
+impl Mask {
+    /// Reads a mask from the bytes of a PNG image, where black or transparent pixels are walls
+    pub fn from_png(bytes: &[u8]) -> GameResult<Self> {
+        let image = image::load_from_memory_with_format(bytes, image::ImageFormat::PNG)
+            .map_err(|error| GameError::ResourceLoadError(error.to_string()))?
+            .to_rgba();
+        let walls = image
+            .pixels()
+            .map(|pixel| {
+                let [r, g, b, a] = pixel.0;
+                a < 128 || (u16::from(r) + u16::from(g) + u16::from(b)) / 3 < 128
+            })
+            .collect();
+
+        Ok(Self {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            walls,
+        })
+    }
+
+    /// Returns whether or not the provided point is a wall, with the mask stretched over a world
+    /// of the provided size
+    pub fn is_wall(&self, point: Point2<f32>, world_size: (f32, f32)) -> bool {
+        let x = (point.x / world_size.0 * self.width as f32).floor();
+        let y = (point.y / world_size.1 * self.height as f32).floor();
+        if x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32 {
+            return false;
+        }
+        self.walls[y as usize * self.width + x as usize]
+    }
+}
+
+/// The static walls of the world
+#[derive(Default)]
+pub struct Arena {
+    /// The outline of the arena, outside of which is a wall
+    shape: Option<Vec<(f32, f32)>>,
+    /// A grid of walls stretched over the world
+    mask: Option<Mask>,
+    /// The static obstacles within the arena
+    obstacles: Vec<Obstacle>,
+    /// The distance from a wall at which fish begin to steer away from it
+    pub wall_avoidance_distance: f32,
+    /// Whether or not walls block fish from perceiving what is behind them
+    pub walls_block_sight: bool,
+    /// The mesh of the shape and obstacles, which is built the first time the arena is drawn
+    mesh: Option<Mesh>,
+    /// The image of the mask, which is built the first time the arena is drawn
+    mask_image: Option<Image>,
+}
+
+impl Arena {
+    /// Creates a new arena from its configuration and an optional mask that has already been read
+    pub fn new(arena_config: &ArenaConfig, mask: Option<Mask>) -> Self {
+        Self {
+            shape: arena_config.shape.clone(),
+            mask,
+            obstacles: arena_config.obstacles.clone(),
+            wall_avoidance_distance: arena_config.wall_avoidance_distance,
+            walls_block_sight: arena_config.walls_block_sight,
+            mesh: None,
+            mask_image: None,
+        }
+    }
+
+    /// Returns whether or not the arena has any walls at all
+    pub fn has_walls(&self) -> bool {
+        self.shape.is_some() || self.mask.is_some() || !self.obstacles.is_empty()
+    }
+
+    /// Returns whether or not the provided point is within a wall of a world of the provided size
+    pub fn is_wall(&self, point: Point2<f32>, world_size: (f32, f32)) -> bool {
+        if let Some(shape) = &self.shape {
+            if !polygon_contains(shape, point) {
+                return true;
+            }
+        }
+        if let Some(mask) = &self.mask {
+            if mask.is_wall(point, world_size) {
+                return true;
+            }
+        }
+        self.obstacles
+            .iter()
+            .any(|obstacle| obstacle.contains(point))
+    }
+
+    /// Draws the walls of the arena in the `ggez` window, stretched over a world of the provided
+    /// size
+    pub fn draw(&mut self, ctx: &mut Context, world_size: (f32, f32)) -> GameResult {
+        if self.mask_image.is_none() {
+            if let Some(mask) = &self.mask {
+                // Walls are opaque and open water is transparent
+                let (r, g, b) = Color::from(WALL_COLOR).to_rgb();
+                let rgba: Vec<u8> = mask
+                    .walls
+                    .iter()
+                    .flat_map(|&wall| vec![r, g, b, if wall { 255 } else { 0 }])
+                    .collect();
+                let mut image =
+                    Image::from_rgba8(ctx, mask.width as u16, mask.height as u16, &rgba)?;
+                image.set_filter(FilterMode::Nearest);
+                self.mask_image = Some(image);
+            }
+        }
+        if self.mesh.is_none() && (self.shape.is_some() || !self.obstacles.is_empty()) {
+            let mut builder = MeshBuilder::new();
+            if let Some(shape) = &self.shape {
+                let points: Vec<Point2<f32>> = shape
+                    .iter()
+                    .map(|point| Point2::new(point.0, point.1))
+                    .collect();
+                builder.polygon(DrawMode::stroke(4.0), &points, WALL_COLOR.into())?;
+            }
+            for obstacle in self.obstacles.iter() {
+                match obstacle {
+                    Obstacle::Circle { center, radius } => {
+                        builder.circle(
+                            DrawMode::fill(),
+                            Point2::new(center.0, center.1),
+                            *radius,
+                            1.0,
+                            WALL_COLOR.into(),
+                        );
+                    }
+                    Obstacle::Polygon { points } => {
+                        let points: Vec<Point2<f32>> = points
+                            .iter()
+                            .map(|point| Point2::new(point.0, point.1))
+                            .collect();
+                        builder.polygon(DrawMode::fill(), &points, WALL_COLOR.into())?;
+                    }
+                }
+            }
+            self.mesh = Some(builder.build(ctx)?);
+        }
+
+        if let Some(image) = &self.mask_image {
+            let scale = Vector2::new(
+                world_size.0 / f32::from(image.width()),
+                world_size.1 / f32::from(image.height()),
+            );
+            graphics::draw(ctx, image, DrawParam::default().scale(scale))?;
+        }
+        if let Some(mesh) = &self.mesh {
+            graphics::draw(ctx, mesh, DrawParam::default())?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether or not the provided point is within the polygon with the provided points,
+/// based on the number of polygon edges a ray from the point crosses
+fn polygon_contains(points: &[(f32, f32)], point: Point2<f32>) -> bool {
+    let mut inside = false;
+    let mut previous = match points.last() {
+        Some(previous) => previous,
+        None => return false,
+    };
+    for current in points.iter() {
+        if (current.1 > point.y) != (previous.1 > point.y)
+            && point.x
+                < (previous.0 - current.0) * (point.y - current.1) / (previous.1 - current.1)
+                    + current.0
+        {
+            inside = !inside;
+        }
+        previous = current;
+    }
+    inside
+}
+
+/// Testing whether points are within a concave polygon
+#[test]
+fn test_polygon_contains() {
+    // An L-shaped polygon
+    let points = [
+        (0.0, 0.0),
+        (10.0, 0.0),
+        (10.0, 5.0),
+        (5.0, 5.0),
+        (5.0, 10.0),
+        (0.0, 10.0),
+    ];
+    assert!(polygon_contains(&points, Point2::new(2.0, 2.0)));
+    assert!(polygon_contains(&points, Point2::new(2.0, 8.0)));
+    assert!(!polygon_contains(&points, Point2::new(8.0, 8.0)));
+    assert!(!polygon_contains(&points, Point2::new(-1.0, 2.0)));
+}
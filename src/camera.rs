@@ -0,0 +1,119 @@
+//! A module for viewing a part of the world through the `ggez` window.
+
+use ggez::{
+    graphics::Rect,
+    nalgebra::{Point2, Vector2},
+};
+
+/// The smallest zoom factor of the camera, relative to fitting the whole world in the window
+const MIN_ZOOM: f32 = 0.25;
+/// The largest zoom factor of the camera, relative to fitting the whole world in the window
+const MAX_ZOOM: f32 = 20.0;
+
+/// A view into the world that can be panned, zoomed and set to follow a fish
+#[derive(Debug, Clone)]
+pub struct Camera {
+    /// The point in the world that is at the center of the window
+    pub center: Point2<f32>,
+    /// How far the camera is zoomed in.
+    /// A zoom of 1 fits the whole world within the window.
+    pub zoom: f32,
+    /// The id of the fish that the camera keeps centered, if any
+    pub following: Option<u64>,
+}
+
+impl Camera {
+    /// Creates a new camera that fits the whole world of the provided size within the window
+    pub fn new(world_size: (f32, f32)) -> Self {
+        Self {
+            center: Point2::new(world_size.0 / 2.0, world_size.1 / 2.0),
+            zoom: 1.0,
+            following: None,
+        }
+    }
+
+    /// Moves the camera back within a world that was resized to the provided size, while keeping
+    /// its zoom and the fish it follows
+    pub fn keep_within(&mut self, world_size: (f32, f32)) {
+        self.center = Point2::new(
+            self.center.x.clamp(0.0, world_size.0),
+            self.center.y.clamp(0.0, world_size.1),
+        );
+    }
+
+    /// Returns the number of window pixels per unit of the world
+    pub fn scale(&self, window_size: (f32, f32), world_size: (f32, f32)) -> f32 {
+        // The world is scaled to fit within the window while keeping its aspect ratio
+        let fit_scale = (window_size.0 / world_size.0).min(window_size.1 / world_size.1);
+        fit_scale * self.zoom
+    }
+
+    /// Returns the area of the world that is visible in the window
+    pub fn view(&self, window_size: (f32, f32), world_size: (f32, f32)) -> Rect {
+        let scale = self.scale(window_size, world_size);
+        let (view_width, view_height) = (window_size.0 / scale, window_size.1 / scale);
+        Rect::new(
+            self.center.x - view_width / 2.0,
+            self.center.y - view_height / 2.0,
+            view_width,
+            view_height,
+        )
+    }
+
+    /// Converts a point in the window to the point in the world that is displayed there
+    pub fn screen_to_world(
+        &self,
+        screen_pos: Point2<f32>,
+        window_size: (f32, f32),
+        world_size: (f32, f32),
+    ) -> Point2<f32> {
+        let view = self.view(window_size, world_size);
+        let scale = self.scale(window_size, world_size);
+        Point2::new(view.x + screen_pos.x / scale, view.y + screen_pos.y / scale)
+    }
+
+    /// Moves the camera by the provided distance in window pixels.
+    /// Panning stops the camera from following a fish.
+    pub fn pan(
+        &mut self,
+        screen_delta: Vector2<f32>,
+        window_size: (f32, f32),
+        world_size: (f32, f32),
+    ) {
+        let scale = self.scale(window_size, world_size);
+        self.center -= screen_delta / scale;
+        self.following = None;
+    }
+
+    /// Zooms the camera by the provided factor, while keeping the point of the world under the
+    /// provided window position in place
+    pub fn zoom_at(
+        &mut self,
+        screen_pos: Point2<f32>,
+        factor: f32,
+        window_size: (f32, f32),
+        world_size: (f32, f32),
+    ) {
+        let anchor = self.screen_to_world(screen_pos, window_size, world_size);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        // The anchor drifts from the provided position when zooming, so the camera is moved by
+        // the amount that it drifted
+        let drifted = self.screen_to_world(screen_pos, window_size, world_size);
+        self.center += anchor - drifted;
+    }
+}
+
+/// Testing that zooming keeps the point under the cursor in place
+#[test]
+fn test_zoom_at() {
+    let mut camera = Camera::new((200.0, 100.0));
+    let (window_size, world_size) = ((400.0, 200.0), (200.0, 100.0));
+    let screen_pos = Point2::new(100.0, 50.0);
+    let before = camera.screen_to_world(screen_pos, window_size, world_size);
+    assert_eq!(before, Point2::new(50.0, 25.0));
+
+    camera.zoom_at(screen_pos, 2.0, window_size, world_size);
+    let after = camera.screen_to_world(screen_pos, window_size, world_size);
+    assert!((after - before).magnitude() < 0.001);
+    assert_eq!(camera.zoom, 2.0);
+}
@@ -0,0 +1,257 @@
+//! A module for plotting the history of the fish population as graphs in a `ggez` window.
+
+use ggez::{
+    graphics,
+    graphics::{Color, DrawMode, DrawParam, MeshBuilder, Rect, Text},
+    nalgebra::Point2,
+    Context, GameResult,
+};
+use std::collections::VecDeque;
+
+use super::{
+    dna::GENE_NAMES,
+    fish::Fish,
+    hud::{PANEL_COLOR, PANEL_MARGIN},
+    stats::Statistics,
+};
+
+/// The height of the charts in window pixels
+pub const CHART_HEIGHT: f32 = 160.0;
+
+/// The height of the title above each chart in window pixels
+const TITLE_HEIGHT: f32 = 20.0;
+
+/// The number of bars in the histogram of a gene's distribution
+const HISTOGRAM_BINS: usize = 20;
+
+/// The RGBA colors of the lines of each fish group, which are reused if there are more groups
+const GROUP_COLORS: [[f32; 4]; 6] = [
+    [0.3, 0.8, 1.0, 1.0],
+    [1.0, 0.8, 0.2, 1.0],
+    [1.0, 0.3, 0.3, 1.0],
+    [0.6, 1.0, 0.4, 1.0],
+    [0.9, 0.5, 1.0, 1.0],
+    [1.0, 1.0, 1.0, 1.0],
+];
+
+/// The RGBA color of the line of a gene's mean and the bars of its histogram
+const GENE_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+
+/// The statistics of the most recent ticks of the simulation
+pub struct History {
+    /// The largest number of ticks that are kept
+    capacity: usize,
+    /// The statistics of each tick, from oldest to newest
+    samples: VecDeque<Statistics>,
+}
+
+impl History {
+    /// Creates a new empty history that keeps the provided number of most recent ticks
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Records the statistics of a tick, forgetting the oldest tick if the history is full
+    pub fn record(&mut self, statistics: Statistics) {
+        if self.samples.len() >= self.capacity {
+            self.samples.pop_front();
+        }
+        if self.capacity > 0 {
+            self.samples.push_back(statistics);
+        }
+    }
+
+    /// Returns the statistics of each tick, from oldest to newest
+    pub fn samples(&self) -> &VecDeque<Statistics> {
+        &self.samples
+    }
+}
+
+/// Draws the population of each group and the mean of the provided gene over the recorded
+/// history as scrolling line graphs, along with a histogram of the gene's current distribution,
+/// along the bottom of the window.
+/// This is expected to be drawn with the screen coordinates set to the window's pixels.
+pub fn draw_charts(
+    ctx: &mut Context,
+    history: &History,
+    fish_groups: &[Vec<Fish>],
+    gene_index: usize,
+) -> GameResult {
+    let (window_width, window_height) = graphics::drawable_size(ctx);
+    let chart_width = (window_width - 4.0 * PANEL_MARGIN) / 3.0;
+    let top = window_height - PANEL_MARGIN - CHART_HEIGHT;
+    let panes: Vec<Rect> = (0..3)
+        .map(|pane_index| {
+            Rect::new(
+                PANEL_MARGIN + pane_index as f32 * (chart_width + PANEL_MARGIN),
+                top,
+                chart_width,
+                CHART_HEIGHT,
+            )
+        })
+        .collect();
+
+    let mut builder = MeshBuilder::new();
+    for pane in panes.iter() {
+        builder.rectangle(DrawMode::fill(), *pane, PANEL_COLOR.into());
+    }
+
+    // The area of each pane below its title and within its margin
+    let plots: Vec<Rect> = panes
+        .iter()
+        .map(|pane| {
+            Rect::new(
+                pane.x + PANEL_MARGIN,
+                pane.y + TITLE_HEIGHT,
+                pane.w - 2.0 * PANEL_MARGIN,
+                pane.h - TITLE_HEIGHT - PANEL_MARGIN,
+            )
+        })
+        .collect();
+
+    let samples = history.samples();
+    let group_count = fish_groups.len();
+
+    // The population of each group, scaled so that the largest population reaches the top
+    let populations: Vec<Vec<f32>> = (0..group_count)
+        .map(|group_index| {
+            samples
+                .iter()
+                .map(|sample| {
+                    sample
+                        .group_populations
+                        .get(group_index)
+                        .copied()
+                        .unwrap_or(0) as f32
+                })
+                .collect()
+        })
+        .collect();
+    let max_population = populations.iter().flatten().cloned().fold(1.0, f32::max);
+    for (group_index, series) in populations.iter().enumerate() {
+        let color = GROUP_COLORS[group_index % GROUP_COLORS.len()];
+        add_line_graph(&mut builder, plots[0], series, (0.0, max_population), color)?;
+    }
+
+    // The mean of the gene, scaled between its lowest and highest recorded means
+    let gene_means: Vec<f32> = samples
+        .iter()
+        .map(|sample| sample.gene_means[gene_index])
+        .collect();
+    let gene_mean_range = value_range(&gene_means);
+    add_line_graph(
+        &mut builder,
+        plots[1],
+        &gene_means,
+        gene_mean_range,
+        GENE_COLOR,
+    )?;
+
+    // The current distribution of the gene across every fish
+    let genes: Vec<f32> = fish_groups
+        .iter()
+        .flatten()
+        .map(|fish| fish.dna()[gene_index])
+        .collect();
+    let gene_range = value_range(&genes);
+    add_histogram(&mut builder, plots[2], &genes, gene_range);
+
+    let mesh = builder.build(ctx)?;
+    graphics::draw(ctx, &mesh, DrawParam::default())?;
+
+    let gene_name = GENE_NAMES[gene_index];
+    let titles = [
+        format!("Population (max {})", max_population),
+        format!(
+            "Mean {} ({:.2} to {:.2})",
+            gene_name, gene_mean_range.0, gene_mean_range.1
+        ),
+        format!(
+            "{} distribution ({:.2} to {:.2})",
+            gene_name, gene_range.0, gene_range.1
+        ),
+    ];
+    for (pane, title) in panes.iter().zip(titles.iter()) {
+        graphics::draw(
+            ctx,
+            &Text::new(title.as_str()),
+            (
+                Point2::new(pane.x + PANEL_MARGIN, pane.y + PANEL_MARGIN),
+                graphics::WHITE,
+            ),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Returns the lowest and highest of the provided values, widened slightly if they are equal so
+/// that they can be scaled between
+fn value_range(values: &[f32]) -> (f32, f32) {
+    let min = values.iter().cloned().fold(f32::INFINITY, f32::min);
+    let max = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    if values.is_empty() {
+        (0.0, 1.0)
+    } else if max <= min {
+        (min - 0.5, max + 0.5)
+    } else {
+        (min, max)
+    }
+}
+
+/// Adds a line graph of the provided values to the mesh builder, with the values spread evenly
+/// across the width of the plot and scaled between the provided range along its height
+fn add_line_graph(
+    builder: &mut MeshBuilder,
+    plot: Rect,
+    values: &[f32],
+    range: (f32, f32),
+    color: [f32; 4],
+) -> GameResult {
+    if values.len() < 2 {
+        return Ok(());
+    }
+    let points: Vec<Point2<f32>> = values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let x = plot.x + plot.w * index as f32 / (values.len() - 1) as f32;
+            let y = plot.y + plot.h * (1.0 - (value - range.0) / (range.1 - range.0));
+            Point2::new(x, y)
+        })
+        .collect();
+    builder.line(&points, 1.5, Color::from(color))?;
+    Ok(())
+}
+
+/// Adds a histogram of the provided values to the mesh builder, with the bars spread between
+/// the provided range across the width of the plot and scaled to the tallest bar along its height
+fn add_histogram(builder: &mut MeshBuilder, plot: Rect, values: &[f32], range: (f32, f32)) {
+    let mut bins = [0usize; HISTOGRAM_BINS];
+    for value in values.iter() {
+        let bin = ((value - range.0) / (range.1 - range.0) * HISTOGRAM_BINS as f32) as usize;
+        bins[bin.min(HISTOGRAM_BINS - 1)] += 1;
+    }
+
+    let tallest = bins.iter().cloned().max().unwrap_or(0).max(1);
+    let bar_width = plot.w / HISTOGRAM_BINS as f32;
+    for (bin_index, &count) in bins.iter().enumerate() {
+        if count == 0 {
+            continue;
+        }
+        let bar_height = plot.h * count as f32 / tallest as f32;
+        builder.rectangle(
+            DrawMode::fill(),
+            Rect::new(
+                plot.x + bin_index as f32 * bar_width + 1.0,
+                plot.y + plot.h - bar_height,
+                bar_width - 2.0,
+                bar_height,
+            ),
+            GENE_COLOR.into(),
+        );
+    }
+}
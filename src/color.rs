@@ -0,0 +1,185 @@
+//! A module for coloring fish by their traits so that selection pressure becomes visible.
+
+use ggez::{
+    graphics,
+    graphics::{Color, DrawMode, DrawParam, MeshBuilder, Rect, Text},
+    nalgebra::Point2,
+    Context, GameResult,
+};
+
+use super::{
+    dna::{GENE_COUNT, GENE_NAMES},
+    fish::Fish,
+    hud::{PANEL_COLOR, PANEL_MARGIN},
+};
+
+/// The colors of the color map from its lowest to its highest value, which are blended between
+const COLOR_MAP: [[f32; 3]; 5] = [
+    [0.27, 0.0, 0.33],
+    [0.23, 0.32, 0.55],
+    [0.13, 0.57, 0.55],
+    [0.37, 0.79, 0.38],
+    [0.99, 0.91, 0.15],
+];
+
+/// The width of the legend's color bar in window pixels
+const LEGEND_WIDTH: f32 = 200.0;
+
+/// The height of the legend's color bar in window pixels
+const LEGEND_BAR_HEIGHT: f32 = 12.0;
+
+/// The number of steps that the legend's color bar is drawn with
+const LEGEND_STEPS: usize = 40;
+
+/// What the color of each fish represents
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorMode {
+    /// Each fish is colored by the id of the spawned fish that its lineage started from, so that
+    /// fish of the same lineage share a color
+    Lineage,
+    /// Each fish is colored by the value of the gene at the provided index of its DNA
+    Gene(usize),
+    /// Each fish is colored by its health
+    Health,
+    /// Each fish is colored by its age
+    Age,
+}
+
+impl ColorMode {
+    /// Returns the next color mode, to cycle through every color mode
+    pub fn next(self) -> Self {
+        match self {
+            ColorMode::Lineage => ColorMode::Gene(0),
+            ColorMode::Gene(gene_index) if gene_index + 1 < GENE_COUNT => {
+                ColorMode::Gene(gene_index + 1)
+            }
+            ColorMode::Gene(_) => ColorMode::Health,
+            ColorMode::Health => ColorMode::Age,
+            ColorMode::Age => ColorMode::Lineage,
+        }
+    }
+
+    /// Returns the name of what the color represents
+    pub fn name(self) -> String {
+        match self {
+            ColorMode::Lineage => "lineage".to_string(),
+            ColorMode::Gene(gene_index) => GENE_NAMES[gene_index].to_string(),
+            ColorMode::Health => "health".to_string(),
+            ColorMode::Age => "age".to_string(),
+        }
+    }
+
+    /// Returns the value of the fish that is colored, or nothing if the fish keeps its own color
+    pub fn value(self, fish: &Fish) -> Option<f32> {
+        match self {
+            ColorMode::Lineage => Some(fish.lineage() as f32),
+            ColorMode::Gene(gene_index) => Some(fish.dna()[gene_index]),
+            ColorMode::Health => Some(fish.health()),
+            ColorMode::Age => Some(fish.age() as f32),
+        }
+    }
+
+    /// Returns the range of values that the color map spans for the provided fish groups.
+    /// Health always spans from empty to full health, while lineages, genes and age span the
+    /// lowest to the highest value in the population.
+    pub fn range(self, fish_groups: &[Vec<Fish>]) -> (f32, f32) {
+        if self == ColorMode::Health {
+            return (0.0, 1.0);
+        }
+        let values = fish_groups
+            .iter()
+            .flatten()
+            .filter_map(|fish| self.value(fish));
+        let (min, max) = values.fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| {
+            (min.min(value), max.max(value))
+        });
+        if min > max {
+            (0.0, 1.0)
+        } else {
+            (min, max)
+        }
+    }
+
+    /// Returns the color of the fish based on where its value falls within the provided range,
+    /// or nothing if the fish keeps its own color
+    pub fn color(self, fish: &Fish, range: (f32, f32)) -> Option<Color> {
+        self.value(fish).map(|value| {
+            let t = if range.1 > range.0 {
+                (value - range.0) / (range.1 - range.0)
+            } else {
+                0.5
+            };
+            color_map(t)
+        })
+    }
+}
+
+/// Returns the color at the provided point between 0 and 1 of the color map
+pub fn color_map(t: f32) -> Color {
+    let position = t.clamp(0.0, 1.0) * (COLOR_MAP.len() - 1) as f32;
+    let index = (position.floor() as usize).min(COLOR_MAP.len() - 2);
+    let blend = position - index as f32;
+    let (from, to) = (COLOR_MAP[index], COLOR_MAP[index + 1]);
+    Color::new(
+        from[0] + (to[0] - from[0]) * blend,
+        from[1] + (to[1] - from[1]) * blend,
+        from[2] + (to[2] - from[2]) * blend,
+        1.0,
+    )
+}
+
+/// Draws a legend of the color map and the range of values it spans in the bottom-right corner
+/// of the window, raised by the provided number of pixels.
+/// This is expected to be drawn with the screen coordinates set to the window's pixels.
+pub fn draw_legend(
+    ctx: &mut Context,
+    color_mode: ColorMode,
+    range: (f32, f32),
+    raised_by: f32,
+) -> GameResult {
+    let title = Text::new(format!(
+        "Color: {} ({:.2} to {:.2})",
+        color_mode.name(),
+        range.0,
+        range.1
+    ));
+    let (title_width, title_height) = title.dimensions(ctx);
+    let width = LEGEND_WIDTH.max(title_width as f32) + 2.0 * PANEL_MARGIN;
+    let height = title_height as f32 + LEGEND_BAR_HEIGHT + 3.0 * PANEL_MARGIN;
+
+    let (window_width, window_height) = graphics::drawable_size(ctx);
+    let left = window_width - PANEL_MARGIN - width;
+    let top = window_height - PANEL_MARGIN - raised_by - height;
+
+    let mut builder = MeshBuilder::new();
+    builder.rectangle(
+        DrawMode::fill(),
+        Rect::new(left, top, width, height),
+        PANEL_COLOR.into(),
+    );
+    let step_width = (width - 2.0 * PANEL_MARGIN) / LEGEND_STEPS as f32;
+    for step in 0..LEGEND_STEPS {
+        builder.rectangle(
+            DrawMode::fill(),
+            Rect::new(
+                left + PANEL_MARGIN + step as f32 * step_width,
+                top + title_height as f32 + 2.0 * PANEL_MARGIN,
+                step_width,
+                LEGEND_BAR_HEIGHT,
+            ),
+            color_map(step as f32 / (LEGEND_STEPS - 1) as f32),
+        );
+    }
+    let mesh = builder.build(ctx)?;
+    graphics::draw(ctx, &mesh, DrawParam::default())?;
+    graphics::draw(
+        ctx,
+        &title,
+        (
+            Point2::new(left + PANEL_MARGIN, top + PANEL_MARGIN),
+            graphics::WHITE,
+        ),
+    )?;
+
+    Ok(())
+}
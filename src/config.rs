@@ -0,0 +1,849 @@
+//! A module for reading the configuration of the simulation and overriding parts of it.
+
+use ron::ser::{to_string_pretty, PrettyConfig};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{fs, path::Path};
+
+use super::{
+    arena::{ArenaConfig, Obstacle},
+    controller::ControllerConfig,
+    dna::{MutationOperator, GENE_NAMES},
+    error::{ConfigProblem, Error},
+    fish::FishConfig,
+    food::FoodConfig,
+    generation::{GenerationalConfig, Selection},
+    species::SpeciationConfig,
+    world::BoundaryMode,
+};
+
+/// The prefix of the environment variables that override fields of the configuration
+pub const ENV_PREFIX: &str = "EVOLUTION_";
+
+/// The fields of the configuration that can be changed while the simulation runs, along with
+/// every field nested within them.
+/// Changes to any other field only take effect once the simulation is restarted.
+pub const RELOADABLE_FIELDS: [&str; 12] = [
+    "show_fps",
+    "show_stats",
+    "ticks_per_second",
+    "boundary_padding",
+    "arena.wall_avoidance_distance",
+    "arena.walls_block_sight",
+    "fish.eating_radius",
+    "fish.mutation_rate",
+    "fish.self_adaptation",
+    "fish.frames_per_animation_frame",
+    "fish.genes",
+    "food.spawn_rate",
+];
+
+/// The fields that differed between the running configuration and a reloaded one
+#[derive(Debug, Default)]
+pub struct ConfigChanges {
+    /// The paths of the fields that were changed in the running simulation
+    pub applied: Vec<String>,
+    /// The paths of the fields that were left unchanged, as they need a restart to change
+    pub needs_restart: Vec<String>,
+}
+
+/// The configuration structure that is read and deserialized from a RON, TOML or JSON file
+/// such as `config.ron`.
+/// Any field that is left out takes its value from `Config::default()`, so a partial
+/// configuration only needs the fields that differ.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// Whether or not the window is fullscreen
+    pub fullscreen: bool,
+    /// If the window is not fullscreen, the size of the window will be specified based on the
+    /// provided (width, height)
+    pub window_size: (f32, f32),
+    /// The (width, height) of the world that the fish and food live in.
+    /// If not provided, the world is the size of the window and is resized along with it.
+    /// Otherwise the world keeps this size and is scaled to fit within the window.
+    pub world_size: Option<(f32, f32)>,
+    /// Whether or not the current FPS should be displayed in the simulation window
+    pub show_fps: bool,
+    /// Whether or not statistics of the fish population should be displayed in the simulation
+    /// window
+    pub show_stats: bool,
+    /// The number of timesteps of the simulation that are run per second, regardless of the
+    /// frame rate of the window
+    pub ticks_per_second: u32,
+    /// The number of most recent ticks that are plotted in the charts
+    pub chart_history: usize,
+    /// The thickness of the padding boundary for the fish around the world
+    pub boundary_padding: f32,
+    /// How the fish are kept within the world
+    pub boundary_mode: BoundaryMode,
+    /// The configuration pertaining to the walls and obstacles of the arena
+    pub arena: ArenaConfig,
+    /// The configuration pertaining to the fish
+    pub fish: FishConfig,
+    /// The configuration pertaining to the food
+    pub food: FoodConfig,
+    /// If provided, the fish are evolved in generations of a fixed number of ticks, each bred
+    /// from the fittest fish of the last, rather than by reproducing as they live
+    pub generational: Option<GenerationalConfig>,
+    /// If provided, the fish of each group are regularly clustered into species by genetic
+    /// distance, whose births and extinctions are tracked
+    pub speciation: Option<SpeciationConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            fullscreen: false,
+            window_size: (1280.0, 640.0),
+            world_size: None,
+            show_fps: true,
+            show_stats: true,
+            ticks_per_second: 60,
+            chart_history: 2000,
+            boundary_padding: 20.0,
+            boundary_mode: BoundaryMode::Steer,
+            arena: ArenaConfig::default(),
+            fish: FishConfig::default(),
+            food: FoodConfig::default(),
+            generational: None,
+            speciation: None,
+        }
+    }
+}
+
+impl Config {
+    /// Reads and deserializes the configuration from the file at the provided path, which is
+    /// read as TOML or JSON if its extension is `.toml` or `.json`, and as RON otherwise
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        read_file(path)
+    }
+
+    /// Returns the size of the world when the simulation runs without a window, which is the
+    /// size that the window would have been
+    pub fn headless_world_size(&self) -> (f32, f32) {
+        self.world_size.unwrap_or(self.window_size)
+    }
+
+    /// Returns the configuration serialized as RON, which can be read back with `Config::load`
+    pub fn to_ron(&self) -> Result<String, Error> {
+        to_string_pretty(self, PrettyConfig::default()).map_err(|e| Error::Serialize(e.to_string()))
+    }
+
+    /// Overrides a single field of the configuration from an assignment of the form
+    /// `path.to.field=value`, such as `fish.mutation_rate=0.05`.
+    ///
+    /// The value is read as JSON, so lists and tuples are written as `[1, 2]`, `None` as `null`,
+    /// and enum variants with fields as `{"Gaussian": {"sigma": 0.2}}`.
+    /// A value that isn't valid JSON is read as a string, so unit enum variants can be written
+    /// by name, such as `boundary_mode=Wrap`.
+    pub fn apply_override(&mut self, assignment: &str) -> Result<(), Error> {
+        let mut parts = assignment.splitn(2, '=');
+        let result = match (parts.next(), parts.next()) {
+            (Some(path), Some(value)) if !path.trim().is_empty() => {
+                self.set_field(path.trim(), value.trim())
+            }
+            _ => Err("expected `path.to.field=value`".to_string()),
+        };
+        result.map_err(|message| Error::Override {
+            assignment: assignment.to_string(),
+            message,
+        })
+    }
+
+    /// Overrides fields of the configuration from the provided environment variables whose
+    /// names start with `ENV_PREFIX`, such as `EVOLUTION_FISH__MUTATION_RATE=0.05` for
+    /// `fish.mutation_rate`.
+    /// After the prefix, a double underscore separates nested fields and the name is lowercased.
+    /// Values are read the same way as in `Config::apply_override`.
+    pub fn apply_env_overrides(
+        &mut self,
+        vars: impl Iterator<Item = (String, String)>,
+    ) -> Result<(), Error> {
+        // Variables are applied in order of their names, so that the result doesn't depend on
+        // the order of the environment
+        let mut vars: Vec<(String, String)> = vars
+            .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+            .collect();
+        vars.sort();
+        for (name, value) in vars {
+            let path = name[ENV_PREFIX.len()..].to_lowercase().replace("__", ".");
+            self.set_field(&path, value.trim())
+                .map_err(|message| Error::Override {
+                    assignment: format!("{}={}", name, value),
+                    message,
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Sets the field at the provided path to the provided value, read as JSON or else as a
+    /// string
+    fn set_field(&mut self, path: &str, value: &str) -> Result<(), String> {
+        let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.into()));
+
+        // The configuration is converted to a tree of values so that any field can be reached
+        // by its path, and converted back so that the new value is checked against its type
+        let mut tree = serde_json::to_value(&*self).map_err(|e| e.to_string())?;
+        let mut field = &mut tree;
+        for key in path.split('.') {
+            field = field
+                .as_object_mut()
+                .and_then(|object| object.get_mut(key))
+                .ok_or_else(|| format!("unknown config field `{}`", path))?;
+        }
+        *field = value;
+
+        *self = serde_json::from_value(tree).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Applies the fields of the provided configuration that can be changed while the simulation
+    /// runs (see `RELOADABLE_FIELDS`), and reports which other fields differ and need a restart
+    pub fn reload(&mut self, reloaded: &Config) -> Result<ConfigChanges, Error> {
+        let mut tree = serde_json::to_value(&*self).map_err(|e| Error::Serialize(e.to_string()))?;
+        let reloaded_tree =
+            serde_json::to_value(reloaded).map_err(|e| Error::Serialize(e.to_string()))?;
+
+        let mut changed = Vec::new();
+        changed_fields(&tree, &reloaded_tree, "", &mut changed);
+
+        let mut changes = ConfigChanges::default();
+        for path in changed {
+            let reloadable = RELOADABLE_FIELDS
+                .iter()
+                .any(|field| path == *field || path.starts_with(&format!("{}.", field)));
+            if !reloadable {
+                changes.needs_restart.push(path);
+                continue;
+            }
+            if let (Some(field), Some(value)) = (
+                tree.pointer_mut(&pointer(&path)),
+                reloaded_tree.pointer(&pointer(&path)),
+            ) {
+                *field = value.clone();
+            }
+            changes.applied.push(path);
+        }
+
+        *self = serde_json::from_value(tree).map_err(|e| Error::Serialize(e.to_string()))?;
+        Ok(changes)
+    }
+
+    /// Checks every field of the configuration for values that would crash or stall the
+    /// simulation, reporting all of the problems that were found at once
+    pub fn validate(&self) -> Result<(), Error> {
+        let mut problems = Problems::default();
+
+        positive(&mut problems, "window_size.0", self.window_size.0);
+        positive(&mut problems, "window_size.1", self.window_size.1);
+        if let Some(world_size) = self.world_size {
+            positive(&mut problems, "world_size.0", world_size.0);
+            positive(&mut problems, "world_size.1", world_size.1);
+        }
+        if self.ticks_per_second == 0 {
+            problems.push(
+                "ticks_per_second",
+                "the simulation would never run",
+                "use a positive number such as 60",
+            );
+        }
+
+        // Fish and food spawn within the boundary padding, so it must leave some of the world
+        // open. In fullscreen mode the world's size isn't known until the window opens.
+        non_negative(&mut problems, "boundary_padding", self.boundary_padding);
+        let world_size = match (self.world_size, self.fullscreen) {
+            (Some(world_size), _) => Some(world_size),
+            (None, false) => Some(self.window_size),
+            (None, true) => None,
+        };
+        if let Some((width, height)) = world_size {
+            if 2.0 * self.boundary_padding >= width.min(height) {
+                problems.push(
+                    "boundary_padding",
+                    format!(
+                        "a padding of {} on each side covers the whole {}x{} world",
+                        self.boundary_padding, width, height
+                    ),
+                    format!("use a padding below {}", width.min(height) / 2.0),
+                );
+            }
+        }
+
+        self.validate_arena(&mut problems);
+        self.validate_fish(&mut problems);
+        self.validate_generational(&mut problems);
+        if let Some(speciation) = &self.speciation {
+            if speciation.interval == 0 {
+                problems.push(
+                    "speciation.interval",
+                    "the fish would never be clustered into species",
+                    "use a positive number such as 100",
+                );
+            }
+            positive(
+                &mut problems,
+                "speciation.compatibility_threshold",
+                speciation.compatibility_threshold,
+            );
+        }
+
+        probability(&mut problems, "food.spawn_rate", self.food.spawn_rate);
+        ordered(&mut problems, "food.radius_range", self.food.radius_range);
+        non_negative(
+            &mut problems,
+            "food.radius_range.0",
+            self.food.radius_range.0,
+        );
+
+        if problems.0.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidConfig(problems.0))
+        }
+    }
+
+    /// Checks the fields of `Config.arena`
+    fn validate_arena(&self, problems: &mut Problems) {
+        let arena = &self.arena;
+        if let Some(shape) = &arena.shape {
+            if shape.len() < 3 {
+                problems.push(
+                    "arena.shape",
+                    format!("a polygon needs at least 3 points, but has {}", shape.len()),
+                    "add more points, or use `None` to leave the whole world open",
+                );
+            }
+        }
+        for (index, obstacle) in arena.obstacles.iter().enumerate() {
+            let path = format!("arena.obstacles[{}]", index);
+            match obstacle {
+                Obstacle::Circle { radius, .. } => {
+                    positive(problems, &format!("{}.radius", path), *radius)
+                }
+                Obstacle::Polygon { points } if points.len() < 3 => problems.push(
+                    &format!("{}.points", path),
+                    format!(
+                        "a polygon needs at least 3 points, but has {}",
+                        points.len()
+                    ),
+                    "add more points, or remove the obstacle",
+                ),
+                Obstacle::Polygon { .. } => (),
+            }
+        }
+        non_negative(
+            problems,
+            "arena.wall_avoidance_distance",
+            arena.wall_avoidance_distance,
+        );
+    }
+
+    /// Checks the fields of `Config.fish`
+    fn validate_fish(&self, problems: &mut Problems) {
+        let fish = &self.fish;
+        if fish.total_food_chain_links == 0 {
+            problems.push(
+                "fish.total_food_chain_links",
+                "there must be at least one group of fish",
+                "use 1 or more",
+            );
+        } else if fish.quantity < fish.total_food_chain_links {
+            problems.push(
+                "fish.quantity",
+                format!(
+                    "{} fish can't be split into {} groups, so no fish would spawn",
+                    fish.quantity, fish.total_food_chain_links
+                ),
+                format!(
+                    "use at least {} (`fish.total_food_chain_links`)",
+                    fish.total_food_chain_links
+                ),
+            );
+        }
+        non_negative(problems, "fish.eating_radius", fish.eating_radius);
+        probability(problems, "fish.mutation_rate", fish.mutation_rate);
+        strictly_ordered(problems, "fish.scale_range", fish.scale_range);
+        non_negative(problems, "fish.scale_range.0", fish.scale_range.0);
+        ordered(problems, "fish.max_speed_range", fish.max_speed_range);
+        non_negative(problems, "fish.max_speed_range.0", fish.max_speed_range.0);
+        ordered(
+            problems,
+            "fish.max_steering_force_range",
+            fish.max_steering_force_range,
+        );
+        non_negative(
+            problems,
+            "fish.max_steering_force_range.0",
+            fish.max_steering_force_range.0,
+        );
+        non_negative(
+            problems,
+            "fish.frames_per_animation_frame",
+            fish.frames_per_animation_frame,
+        );
+
+        for (name, gene) in GENE_NAMES.iter().zip(fish.genes.as_array().iter()) {
+            let path = format!("fish.genes.{}", name);
+            strictly_ordered(
+                problems,
+                &format!("{}.initial_range", path),
+                gene.initial_range,
+            );
+            ordered(problems, &format!("{}.bounds", path), gene.bounds);
+            // An infinite or NaN step size makes the operator panic when the gene mutates
+            let step = match gene.operator {
+                MutationOperator::Gaussian { sigma } => Some(("Gaussian.sigma", sigma)),
+                MutationOperator::Uniform { amount } => Some(("Uniform.amount", amount)),
+                MutationOperator::Relative { fraction } => Some(("Relative.fraction", fraction)),
+                MutationOperator::Reset => None,
+            };
+            if let Some((name, value)) = step {
+                finite(problems, &format!("{}.operator.{}", path, name), value);
+            }
+        }
+
+        if let ControllerConfig::BehaviourTree(tree) = &fish.controller {
+            let path = "fish.controller.BehaviourTree";
+            if tree.max_depth == 0 {
+                problems.push(
+                    &format!("{}.max_depth", path),
+                    "a tree needs at least one node",
+                    "use a depth of 1 or more, such as 4",
+                );
+            }
+            probability(
+                problems,
+                &format!("{}.subtree_mutation_rate", path),
+                tree.subtree_mutation_rate,
+            );
+            probability(
+                problems,
+                &format!("{}.constant_mutation_rate", path),
+                tree.constant_mutation_rate,
+            );
+            non_negative(
+                problems,
+                &format!("{}.constant_sigma", path),
+                tree.constant_sigma,
+            );
+            non_negative(
+                problems,
+                &format!("{}.distance_coefficient", path),
+                tree.distance_coefficient,
+            );
+        }
+        if let ControllerConfig::Neat(neat) = &fish.controller {
+            let path = "fish.controller.Neat";
+            for &(name, rate) in [
+                ("weight_mutation_rate", neat.weight_mutation_rate),
+                ("add_connection_rate", neat.add_connection_rate),
+                ("add_node_rate", neat.add_node_rate),
+            ]
+            .iter()
+            {
+                probability(problems, &format!("{}.{}", path, name), rate);
+            }
+            for &(name, value) in [
+                ("weight_sigma", neat.weight_sigma),
+                ("excess_coefficient", neat.excess_coefficient),
+                ("disjoint_coefficient", neat.disjoint_coefficient),
+                ("weight_coefficient", neat.weight_coefficient),
+            ]
+            .iter()
+            {
+                non_negative(problems, &format!("{}.{}", path, name), value);
+            }
+        }
+
+        if let Some(self_adaptation) = &fish.self_adaptation {
+            let path = "fish.self_adaptation";
+            strictly_ordered(
+                problems,
+                &format!("{}.initial_mutation_rate_range", path),
+                self_adaptation.initial_mutation_rate_range,
+            );
+            probability(
+                problems,
+                &format!("{}.initial_mutation_rate_range.0", path),
+                self_adaptation.initial_mutation_rate_range.0,
+            );
+            probability(
+                problems,
+                &format!("{}.initial_mutation_rate_range.1", path),
+                self_adaptation.initial_mutation_rate_range.1,
+            );
+            ordered(
+                problems,
+                &format!("{}.mutation_rate_bounds", path),
+                self_adaptation.mutation_rate_bounds,
+            );
+            ordered(
+                problems,
+                &format!("{}.step_bounds", path),
+                self_adaptation.step_bounds,
+            );
+            non_negative(
+                problems,
+                &format!("{}.learning_rate", path),
+                self_adaptation.learning_rate,
+            );
+        }
+
+        if let Some(growth) = &fish.growth {
+            let path = "fish.growth";
+            if !(growth.birth_scale > 0.0 && growth.birth_scale <= 1.0) {
+                problems.push(
+                    &format!("{}.birth_scale", path),
+                    format!(
+                        "{} is not a fraction of the adult scale",
+                        growth.birth_scale
+                    ),
+                    "use a value above 0 and at most 1, such as 0.4",
+                );
+            }
+            non_negative(
+                problems,
+                &format!("{}.growth_per_meal", path),
+                growth.growth_per_meal,
+            );
+            positive(
+                problems,
+                &format!("{}.predation_scale_ratio", path),
+                growth.predation_scale_ratio,
+            );
+            finite(
+                problems,
+                &format!("{}.adult_scale_mutation", path),
+                growth.adult_scale_mutation,
+            );
+        }
+    }
+
+    /// Checks the fields of `Config.generational`, if it is provided
+    fn validate_generational(&self, problems: &mut Problems) {
+        let generational = match &self.generational {
+            Some(generational) => generational,
+            None => return,
+        };
+        if generational.generation_ticks == 0 {
+            problems.push(
+                "generational.generation_ticks",
+                "the fish would never be evaluated",
+                "use a positive number such as 1500",
+            );
+        }
+        probability(
+            problems,
+            "generational.crossover_rate",
+            generational.crossover_rate,
+        );
+        let fitness = &generational.fitness;
+        for &(name, weight) in [
+            ("survival", fitness.survival),
+            ("food_eaten", fitness.food_eaten),
+            ("prey_eaten", fitness.prey_eaten),
+        ]
+        .iter()
+        {
+            finite(problems, &format!("generational.fitness.{}", name), weight);
+        }
+        match generational.selection {
+            Selection::Tournament { size: 0 } => problems.push(
+                "generational.selection.Tournament.size",
+                "a tournament needs at least one fish",
+                "use a size of 1 or more, such as 3",
+            ),
+            Selection::Elitist { fraction } if !(fraction > 0.0 && fraction <= 1.0) => problems
+                .push(
+                    "generational.selection.Elitist.fraction",
+                    format!("{} is not a fraction of the group", fraction),
+                    "use a value above 0 and up to 1, such as 0.2",
+                ),
+            _ => (),
+        }
+        let fish_per_group = self.fish.quantity / self.fish.total_food_chain_links.max(1);
+        if generational.elites >= fish_per_group && fish_per_group > 0 {
+            problems.push(
+                "generational.elites",
+                format!(
+                    "every one of the {} fish in each group would be carried over, so nothing \
+                     would evolve",
+                    fish_per_group
+                ),
+                format!("use fewer than {}", fish_per_group),
+            );
+        }
+    }
+}
+
+/// Reads and deserializes the file at the provided path, which is read as TOML or JSON if its
+/// extension is `.toml` or `.json`, and as RON otherwise
+pub fn read_file<T: DeserializeOwned>(path: &Path) -> Result<T, Error> {
+    let contents = fs::read_to_string(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    let parsed = match extension {
+        // TOML is read through the same tree of values as JSON, so that enums are written the
+        // same way in both, such as `{ Gaussian = { sigma = 0.2 } }`
+        Some("toml") => toml::from_str::<toml::Value>(&contents)
+            .map_err(|e| e.to_string())
+            .and_then(|value| serde_json::to_value(value).map_err(|e| e.to_string()))
+            .and_then(|value| serde_json::from_value(value).map_err(|e| e.to_string())),
+        Some("json") => serde_json::from_str(&contents).map_err(|e| e.to_string()),
+        _ => ron::de::from_str(&contents).map_err(|e| e.to_string()),
+    };
+    parsed.map_err(|message| Error::Parse {
+        path: path.to_path_buf(),
+        message,
+    })
+}
+
+/// Collects the paths of the fields whose values differ between two trees of a configuration.
+/// Fields that are structures are compared field by field, while any other value, such as a
+/// list or an enum, is compared as a whole.
+fn changed_fields(old: &Value, new: &Value, prefix: &str, changed: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(old_fields), Value::Object(new_fields))
+            if !is_enum(old) && !is_enum(new) =>
+        {
+            for (key, old_value) in old_fields.iter() {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                match new_fields.get(key) {
+                    Some(new_value) => changed_fields(old_value, new_value, &path, changed),
+                    None => changed.push(path),
+                }
+            }
+        }
+        _ if old != new => changed.push(prefix.to_string()),
+        _ => (),
+    }
+}
+
+/// Returns whether or not a value in the tree of a configuration is an enum variant with fields,
+/// which is represented as an object with the variant's name as its only key
+fn is_enum(value: &Value) -> bool {
+    match value {
+        Value::Object(fields) => {
+            fields.len() == 1
+                && fields
+                    .keys()
+                    .all(|key| key.starts_with(|c: char| c.is_ascii_uppercase()))
+        }
+        _ => false,
+    }
+}
+
+/// Returns the JSON pointer to the field at the provided path, e.g. `/fish/mutation_rate`
+fn pointer(path: &str) -> String {
+    format!("/{}", path.replace('.', "/"))
+}
+
+/// The problems found while validating a configuration
+#[derive(Default)]
+struct Problems(Vec<ConfigProblem>);
+
+impl Problems {
+    /// Records a problem with the field at the provided path
+    fn push(&mut self, path: &str, problem: impl Into<String>, suggestion: impl Into<String>) {
+        self.0.push(ConfigProblem {
+            path: path.to_string(),
+            problem: problem.into(),
+            suggestion: suggestion.into(),
+        });
+    }
+}
+
+/// Checks that a value is above zero
+fn positive(problems: &mut Problems, path: &str, value: f32) {
+    if value <= 0.0 || value.is_nan() {
+        problems.push(
+            path,
+            format!("{} is not positive", value),
+            "use a value above 0",
+        );
+    }
+}
+
+/// Checks that a value is neither infinite nor NaN
+fn finite(problems: &mut Problems, path: &str, value: f32) {
+    if !value.is_finite() {
+        problems.push(
+            path,
+            format!("{} is not a finite number", value),
+            "use a finite value such as 0.1",
+        );
+    }
+}
+
+/// Checks that a value is zero or above
+fn non_negative(problems: &mut Problems, path: &str, value: f32) {
+    if value < 0.0 || value.is_nan() {
+        problems.push(
+            path,
+            format!("{} is negative", value),
+            "use a value of 0 or above",
+        );
+    }
+}
+
+/// Checks that a value is between zero and one
+fn probability(problems: &mut Problems, path: &str, value: f32) {
+    if !(0.0..=1.0).contains(&value) {
+        problems.push(
+            path,
+            format!("{} is not a probability", value),
+            "use a value between 0 and 1",
+        );
+    }
+}
+
+/// Checks that the minimum of a range is not above its maximum
+fn ordered(problems: &mut Problems, path: &str, range: (f32, f32)) {
+    if range.0 > range.1 || range.0.is_nan() || range.1.is_nan() {
+        problems.push(
+            path,
+            format!(
+                "the minimum ({}) is above the maximum ({})",
+                range.0, range.1
+            ),
+            format!("swap them: ({}, {})", range.1, range.0),
+        );
+    }
+}
+
+/// Checks that the minimum of a range that values are randomly drawn from is below its maximum
+fn strictly_ordered(problems: &mut Problems, path: &str, range: (f32, f32)) {
+    if range.0 == range.1 {
+        problems.push(
+            path,
+            format!("the range is empty, as both ends are {}", range.0),
+            format!("widen it slightly, e.g. ({}, {})", range.0, range.0 + 0.001),
+        );
+    } else {
+        ordered(problems, path, range);
+    }
+}
+
+/// Testing that overrides reach nested fields and are checked against their types
+#[test]
+fn test_apply_override() {
+    let mut config = Config::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("config.ron"))
+        .expect("config.ron should load");
+    config.apply_override("fish.mutation_rate=0.05").unwrap();
+    config.apply_override("boundary_mode=Wrap").unwrap();
+    config.apply_override("world_size=[800, 600]").unwrap();
+    assert_eq!(config.fish.mutation_rate, 0.05);
+    assert_eq!(config.boundary_mode, BoundaryMode::Wrap);
+    assert_eq!(config.world_size, Some((800.0, 600.0)));
+
+    assert!(config.apply_override("fish.unknown=1").is_err());
+    assert!(config.apply_override("fish.quantity=many").is_err());
+    assert!(config.apply_override("fish.quantity").is_err());
+}
+
+/// Testing that every problem with the configuration is reported with the path of its field
+#[test]
+fn test_validate() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("config.ron");
+    let mut config = Config::load(&path).expect("config.ron should load");
+    assert!(config.validate().is_ok());
+
+    config.fish.total_food_chain_links = 0;
+    config.fish.scale_range = (2.0, 0.5);
+    config.ticks_per_second = 0;
+    config.food.spawn_rate = 1.5;
+    let mut generational = GenerationalConfig::default();
+    generational.fitness.survival = f32::NAN;
+    config.generational = Some(generational);
+    config.fish.genes.food_weight.operator = MutationOperator::Uniform {
+        amount: f32::INFINITY,
+    };
+    match config.validate() {
+        Err(Error::InvalidConfig(problems)) => {
+            let paths: Vec<&str> = problems.iter().map(|p| p.path.as_str()).collect();
+            assert_eq!(
+                paths,
+                [
+                    "ticks_per_second",
+                    "fish.total_food_chain_links",
+                    "fish.scale_range",
+                    "fish.genes.food_weight.operator.Uniform.amount",
+                    "generational.fitness.survival",
+                    "food.spawn_rate"
+                ]
+            );
+        }
+        _ => panic!("the config should be invalid"),
+    }
+}
+
+/// Testing that reloading applies runtime-safe fields and reports the others
+#[test]
+fn test_reload() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("config.ron");
+    let mut config = Config::load(&path).expect("config.ron should load");
+    let mut reloaded = Config::load(&path).expect("config.ron should load");
+    reloaded.fish.mutation_rate = 0.5;
+    reloaded.show_fps = !config.show_fps;
+    reloaded.fish.quantity += 1;
+
+    let changes = config.reload(&reloaded).unwrap();
+    assert_eq!(changes.applied, ["fish.mutation_rate", "show_fps"]);
+    assert_eq!(changes.needs_restart, ["fish.quantity"]);
+    assert_eq!(config.fish.mutation_rate, 0.5);
+    assert_eq!(config.fish.quantity, reloaded.fish.quantity - 1);
+}
+
+/// Testing that a partial configuration takes the default value of every field it leaves out
+#[test]
+fn test_partial_config() {
+    let config: Config = ron::de::from_str("(fish: (quantity: 12), boundary_mode: Wrap)")
+        .expect("a partial config should load");
+    assert_eq!(config.fish.quantity, 12);
+    assert_eq!(config.boundary_mode, BoundaryMode::Wrap);
+    assert_eq!(config.fish.total_food_chain_links, 3);
+    assert!(config.validate().is_ok());
+
+    // The serialized configuration can be read back
+    let dumped: Config = ron::de::from_str(&config.to_ron().unwrap()).unwrap();
+    assert_eq!(dumped.fish.quantity, 12);
+}
+
+/// Testing that TOML and JSON configs share the schema of RON configs, and that environment
+/// variables override nested fields
+#[test]
+fn test_formats_and_env_overrides() {
+    let toml_path = std::env::temp_dir().join("evolution_test_config.toml");
+    fs::write(
+        &toml_path,
+        "[fish]\nquantity = 12\n\n[[arena.obstacles]]\nCircle = { center = [1, 2], radius = 3 }\n",
+    )
+    .unwrap();
+    let toml_config = Config::load(&toml_path).unwrap();
+    assert_eq!(toml_config.fish.quantity, 12);
+    assert_eq!(toml_config.arena.obstacles.len(), 1);
+
+    let mut json_config: Config =
+        serde_json::from_str(r#"{"boundary_mode": "Wrap", "world_size": [800, 600]}"#).unwrap();
+    assert_eq!(json_config.boundary_mode, BoundaryMode::Wrap);
+    assert_eq!(json_config.world_size, Some((800.0, 600.0)));
+
+    let vars = vec![
+        (
+            "EVOLUTION_FISH__MUTATION_RATE".to_string(),
+            "0.05".to_string(),
+        ),
+        ("EVOLUTION_BOUNDARY_MODE".to_string(), "Reflect".to_string()),
+        ("HOME".to_string(), "/root".to_string()),
+    ];
+    json_config.apply_env_overrides(vars.into_iter()).unwrap();
+    assert_eq!(json_config.fish.mutation_rate, 0.05);
+    assert_eq!(json_config.boundary_mode, BoundaryMode::Reflect);
+}
@@ -0,0 +1,192 @@
+//! A module for the controllers that decide how a fish steers, based on what it senses.
+
+use ggez::nalgebra::Vector2;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    neat::{Genome, Innovations, NeatConfig},
+    tree::{Node, TreeConfig},
+};
+
+/// The number of values that a fish senses, which are the inputs of its controller
+pub const INPUT_COUNT: usize = 10;
+
+/// The name of each value that a fish senses, in the order they are passed to its controller
+pub const INPUT_NAMES: [&str; INPUT_COUNT] = [
+    "bias",
+    "target_x",
+    "target_y",
+    "has_target",
+    "predator_x",
+    "predator_y",
+    "has_predator",
+    "velocity_x",
+    "velocity_y",
+    "health",
+];
+
+/// The number of values that a network controller outputs, which are the horizontal and
+/// vertical steering force as fractions of the fish's maximum steering force
+pub const OUTPUT_COUNT: usize = 2;
+
+/// The ways that fish can decide how to steer
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub enum ControllerConfig {
+    /// Fish steer towards their target and away from predators, weighted by the
+    /// `food_weight` and `predator_weight` genes
+    #[default]
+    Weights,
+    /// Fish steer with a neural network whose weights and topology evolve
+    Neat(NeatConfig),
+    /// Fish steer with a behaviour tree of primitive behaviours and conditions that evolves by
+    /// genetic programming
+    BehaviourTree(TreeConfig),
+}
+
+impl ControllerConfig {
+    /// Returns the brain of a newly spawned fish
+    pub fn random_brain(&self, innovations: &mut Innovations, rng: &mut StdRng) -> Brain {
+        match self {
+            ControllerConfig::Weights => Brain::Weights,
+            ControllerConfig::Neat(_) => {
+                Brain::Neat(Genome::new(INPUT_COUNT, OUTPUT_COUNT, innovations, rng))
+            }
+            ControllerConfig::BehaviourTree(tree_config) => {
+                Brain::BehaviourTree(Node::random(tree_config.max_depth, rng))
+            }
+        }
+    }
+}
+
+/// The evolvable part of a fish's controller that it passes on to its offspring
+#[derive(Debug, Clone, PartialEq)]
+pub enum Brain {
+    /// The fish steers with the weights in its DNA, so its brain holds nothing else
+    Weights,
+    /// The fish steers with the outputs of a NEAT network
+    Neat(Genome),
+    /// The fish steers with a behaviour tree
+    BehaviourTree(Node),
+}
+
+impl Brain {
+    /// Returns the brain of an offspring of a fish with this brain and an optional mate, which
+    /// is crossed over with this brain before possibly mutating.
+    /// This brain's fish should be the fitter of the two.
+    pub fn offspring(
+        &self,
+        mate: Option<&Brain>,
+        config: &ControllerConfig,
+        innovations: &mut Innovations,
+        rng: &mut StdRng,
+    ) -> Self {
+        match (self, config) {
+            (Brain::Neat(genome), ControllerConfig::Neat(neat_config)) => {
+                let mut genome = match mate {
+                    Some(Brain::Neat(mate_genome)) => genome.crossover(mate_genome, rng),
+                    _ => genome.clone(),
+                };
+                genome.mutate(neat_config, innovations, rng);
+                Brain::Neat(genome)
+            }
+            (Brain::BehaviourTree(tree), ControllerConfig::BehaviourTree(tree_config)) => {
+                let mut tree = match mate {
+                    Some(Brain::BehaviourTree(mate_tree)) => {
+                        tree.crossover(mate_tree, tree_config.max_depth, rng)
+                    }
+                    _ => tree.clone(),
+                };
+                tree.mutate(tree_config, rng);
+                Brain::BehaviourTree(tree)
+            }
+            _ => self.clone(),
+        }
+    }
+
+    /// Returns the distance between this brain and another, which is added to the genetic
+    /// distance between fish when they are clustered into species
+    pub fn distance(&self, other: &Brain, config: &ControllerConfig) -> f32 {
+        match (self, other, config) {
+            (Brain::Neat(genome), Brain::Neat(other), ControllerConfig::Neat(neat_config)) => {
+                genome.distance(other, neat_config)
+            }
+            (
+                Brain::BehaviourTree(tree),
+                Brain::BehaviourTree(other),
+                ControllerConfig::BehaviourTree(tree_config),
+            ) => tree_config.distance_coefficient * tree.difference(other) as f32,
+            _ => 0.0,
+        }
+    }
+
+    /// Returns the steering force as a fraction of the maximum steering force for what the fish
+    /// senses, or nothing if the fish steers with its weights instead
+    pub fn steer(&self, senses: &Senses) -> Option<Vector2<f32>> {
+        match self {
+            Brain::Weights => None,
+            Brain::Neat(genome) => {
+                let outputs = genome.activate(&senses.inputs());
+                Some(Vector2::new(outputs[0], outputs[1]))
+            }
+            Brain::BehaviourTree(tree) => Some(tree.steer(senses)),
+        }
+    }
+
+    /// Returns the lines of text describing the brain, to be displayed when its fish is
+    /// inspected
+    pub fn inspect_lines(&self) -> Vec<String> {
+        match self {
+            Brain::Weights => Vec::new(),
+            Brain::Neat(genome) => vec![format!(
+                "Brain: {} nodes, {} connections",
+                genome.node_count(),
+                genome.connection_count()
+            )],
+            Brain::BehaviourTree(tree) => vec![
+                format!("Brain: {} nodes, depth {}", tree.size(), tree.depth()),
+                tree.to_string(),
+            ],
+        }
+    }
+}
+
+/// What a fish senses, each relative to its own perception and abilities
+#[derive(Debug, Clone, Copy)]
+pub struct Senses {
+    /// The offset to what the fish is heading towards to eat as a fraction of its food
+    /// perception, if anything
+    pub target: Option<Vector2<f32>>,
+    /// The offset to the nearest perceived predator as a fraction of its predator perception,
+    /// if any
+    pub predator: Option<Vector2<f32>>,
+    /// The velocity of the fish as a fraction of its maximum speed
+    pub velocity: Vector2<f32>,
+    /// The health of the fish
+    pub health: f32,
+}
+
+impl Senses {
+    /// Returns the values that are passed to a controller, in the order of `INPUT_NAMES`.
+    /// Offsets are clamped between -1 and 1.
+    pub fn inputs(&self) -> [f32; INPUT_COUNT] {
+        let offset = |offset: Option<Vector2<f32>>| match offset {
+            Some(offset) => [offset.x.clamp(-1.0, 1.0), offset.y.clamp(-1.0, 1.0), 1.0],
+            None => [0.0, 0.0, 0.0],
+        };
+        let target = offset(self.target);
+        let predator = offset(self.predator);
+        [
+            1.0,
+            target[0],
+            target[1],
+            target[2],
+            predator[0],
+            predator[1],
+            predator[2],
+            self.velocity.x,
+            self.velocity.y,
+            self.health,
+        ]
+    }
+}
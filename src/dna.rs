@@ -0,0 +1,357 @@
+//! A module for describing how the genes of a fish are initialized and mutated.
+
+use rand::{rngs::StdRng, Rng};
+use rand_distr::{Distribution, Normal};
+use serde::{Deserialize, Serialize};
+
+/// The number of genes held in the DNA of a fish
+pub const GENE_COUNT: usize = 4;
+
+/// The name of each gene in the DNA of a fish, in the order they are stored
+pub const GENE_NAMES: [&str; GENE_COUNT] = [
+    "food_weight",
+    "predator_weight",
+    "food_perception",
+    "predator_perception",
+];
+
+/// The configuration structure for every gene in the DNA of a fish that is read and
+/// deserialized from `config.ron`.
+/// Any gene that is left out takes its configuration from `GenesConfig::default()`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GenesConfig {
+    /// The weight of attraction towards food and prey
+    pub food_weight: GeneConfig,
+    /// The weight of attraction towards predators
+    pub predator_weight: GeneConfig,
+    /// The radius within which prey is perceived
+    pub food_perception: GeneConfig,
+    /// The radius within which predators are perceived
+    pub predator_perception: GeneConfig,
+}
+
+impl GenesConfig {
+    /// Returns the configuration of each gene, in the order they are stored in the DNA
+    pub fn as_array(&self) -> [&GeneConfig; GENE_COUNT] {
+        [
+            &self.food_weight,
+            &self.predator_weight,
+            &self.food_perception,
+            &self.predator_perception,
+        ]
+    }
+
+    /// Returns the genetic distance between two DNA sequences, which is the mean difference
+    /// between their genes as a fraction of the width of each gene's bounds.
+    /// Genes whose bounds have no width don't add to the distance.
+    pub fn distance(&self, a: &[f32; GENE_COUNT], b: &[f32; GENE_COUNT]) -> f32 {
+        let total: f32 = self
+            .as_array()
+            .iter()
+            .zip(a.iter().zip(b.iter()))
+            .map(|(gene_config, (a, b))| {
+                let width = gene_config.bounds.1 - gene_config.bounds.0;
+                if width > 0.0 {
+                    (a - b).abs() / width
+                } else {
+                    0.0
+                }
+            })
+            .sum();
+        total / GENE_COUNT as f32
+    }
+
+    /// Returns the values expressed by a pair of allele sets, based on the dominance of each
+    /// gene
+    pub fn express(&self, alleles: &[[f32; GENE_COUNT]; 2]) -> [f32; GENE_COUNT] {
+        let mut dna = [0.0; GENE_COUNT];
+        for (gene_index, (gene, gene_config)) in
+            dna.iter_mut().zip(self.as_array().iter()).enumerate()
+        {
+            *gene = gene_config
+                .dominance
+                .express(alleles[0][gene_index], alleles[1][gene_index]);
+        }
+        dna
+    }
+
+    /// Generates a random DNA sequence based on the initial range of each gene
+    pub fn random_dna(&self, rng: &mut StdRng) -> [f32; GENE_COUNT] {
+        let mut dna = [0.0; GENE_COUNT];
+        for (gene, gene_config) in dna.iter_mut().zip(self.as_array().iter()) {
+            *gene = gene_config.random_value(rng);
+        }
+        dna
+    }
+}
+
+impl Default for GenesConfig {
+    /// Weights that start between -2 and 2 and mutate by a small absolute amount, and perception
+    /// radii that start between 10 and 100 and mutate by a fraction of their value
+    fn default() -> Self {
+        let weight = || GeneConfig {
+            initial_range: (-2.0, 2.0),
+            operator: MutationOperator::Gaussian { sigma: 0.1 },
+            bounds: (-5.0, 5.0),
+            bound_mode: BoundMode::Reflect,
+            dominance: Dominance::Additive,
+        };
+        let perception = || GeneConfig {
+            initial_range: (10.0, 100.0),
+            operator: MutationOperator::Relative { fraction: 0.1 },
+            bounds: (0.0, 300.0),
+            bound_mode: BoundMode::Hard,
+            dominance: Dominance::Additive,
+        };
+        Self {
+            food_weight: weight(),
+            predator_weight: weight(),
+            food_perception: perception(),
+            predator_perception: perception(),
+        }
+    }
+}
+
+/// The configuration of how a single gene is initialized, mutated and bounded
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GeneConfig {
+    /// The range of values that a gene of a newly spawned fish will be within.
+    /// The `Reset` mutation operator also draws from this range.
+    pub initial_range: (f32, f32),
+    /// The operator that is applied to the gene when it mutates
+    pub operator: MutationOperator,
+    /// The lowest and highest values the gene may hold
+    pub bounds: (f32, f32),
+    /// How a gene that was mutated outside of its bounds is brought back within them
+    pub bound_mode: BoundMode,
+    /// How the two alleles of the gene of a diploid fish are expressed
+    #[serde(default)]
+    pub dominance: Dominance,
+}
+
+impl GeneConfig {
+    /// Returns the midpoint of the initial range of the gene, at or above which an allele is
+    /// counted as a high allele in the allele frequency statistics
+    pub fn allele_midpoint(&self) -> f32 {
+        (self.initial_range.0 + self.initial_range.1) / 2.0
+    }
+
+    /// Returns a random value within the initial range of the gene
+    pub fn random_value(&self, rng: &mut StdRng) -> f32 {
+        rng.gen_range(self.initial_range.0, self.initial_range.1)
+    }
+
+    /// Applies the mutation operator to the provided gene, and then bounds it
+    pub fn mutate(&self, gene: f32, rng: &mut StdRng) -> f32 {
+        self.mutate_with_step(gene, 1.0, rng)
+    }
+
+    /// Applies the mutation operator to the provided gene with its step size (`sigma`, `amount`
+    /// or `fraction`) multiplied by `step`, and then bounds it
+    pub fn mutate_with_step(&self, gene: f32, step: f32, rng: &mut StdRng) -> f32 {
+        let mutated = match self.operator {
+            MutationOperator::Gaussian { sigma } => {
+                gene + Normal::new(0.0, (sigma * step).abs()).unwrap().sample(rng)
+            }
+            MutationOperator::Uniform { amount } if amount * step != 0.0 => {
+                let amount = (amount * step).abs();
+                gene + rng.gen_range(-amount, amount)
+            }
+            MutationOperator::Relative { fraction } if fraction * step != 0.0 => {
+                let fraction = (fraction * step).abs();
+                gene * (1.0 + rng.gen_range(-fraction, fraction))
+            }
+            // A step size of zero leaves the gene unchanged
+            MutationOperator::Uniform { .. } | MutationOperator::Relative { .. } => gene,
+            MutationOperator::Reset => self.random_value(rng),
+        };
+
+        self.bound_mode.apply(mutated, self.bounds)
+    }
+}
+
+/// The configuration of self-adaptive mutation, where each fish carries its own mutation rate
+/// and step size that are themselves mutated on reproduction, evolution-strategy style.
+/// Any field that is left out takes its value from `SelfAdaptationConfig::default()`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SelfAdaptationConfig {
+    /// The range of mutation rates for newly spawned fish
+    pub initial_mutation_rate_range: (f32, f32),
+    /// The lowest and highest mutation rates a fish may carry
+    pub mutation_rate_bounds: (f32, f32),
+    /// Whether or not the step size of the mutation operators is also self-adaptive.
+    /// If not, every fish mutates its genes with the step sizes in `GenesConfig`.
+    pub adapt_step: bool,
+    /// The lowest and highest multipliers of the mutation operator step sizes a fish may carry
+    pub step_bounds: (f32, f32),
+    /// The learning rate (tau) of the log-normal update applied to the mutation rate and step
+    /// size of offspring.
+    /// E.g. A value of 0.2 changes them by a factor of `e^(0.2 * N(0, 1))`.
+    pub learning_rate: f32,
+}
+
+impl Default for SelfAdaptationConfig {
+    fn default() -> Self {
+        Self {
+            initial_mutation_rate_range: (0.005, 0.05),
+            mutation_rate_bounds: (0.0001, 1.0),
+            adapt_step: true,
+            step_bounds: (0.01, 10.0),
+            learning_rate: 0.2,
+        }
+    }
+}
+
+impl SelfAdaptationConfig {
+    /// Returns a random mutation rate within the initial range
+    pub fn random_mutation_rate(&self, rng: &mut StdRng) -> f32 {
+        rng.gen_range(
+            self.initial_mutation_rate_range.0,
+            self.initial_mutation_rate_range.1,
+        )
+    }
+
+    /// Returns the mutation rate and step size of an offspring, based on those of its parent
+    pub fn adapt(&self, mutation_rate: f32, step: f32, rng: &mut StdRng) -> (f32, f32) {
+        let log_normal = Normal::new(0.0, self.learning_rate.abs()).unwrap();
+        let mutation_rate = BoundMode::Hard.apply(
+            mutation_rate * log_normal.sample(rng).exp(),
+            self.mutation_rate_bounds,
+        );
+        let step = if self.adapt_step {
+            BoundMode::Hard.apply(step * log_normal.sample(rng).exp(), self.step_bounds)
+        } else {
+            step
+        };
+        (mutation_rate, step)
+    }
+}
+
+/// The operators that can be applied to a gene when it mutates
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum MutationOperator {
+    /// Adds a normally distributed value with a mean of 0 and a standard deviation of `sigma`
+    Gaussian { sigma: f32 },
+    /// Adds a uniformly distributed value between `-amount` and `amount`
+    Uniform { amount: f32 },
+    /// Changes the gene by a uniformly distributed percentage of its own value.
+    /// E.g. A fraction of 0.1 changes the gene by up to 10% in either direction.
+    Relative { fraction: f32 },
+    /// Replaces the gene with a random value within its initial range
+    Reset,
+}
+
+/// The ways that the two alleles of a gene can be expressed
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+pub enum Dominance {
+    /// The mean of the two alleles is expressed
+    #[default]
+    Additive,
+    /// The higher allele is expressed, masking the lower one
+    Dominant,
+    /// The higher allele is only expressed when both alleles are high, so the lower allele is
+    /// expressed
+    Recessive,
+}
+
+impl Dominance {
+    /// Returns the value expressed by the provided pair of alleles
+    pub fn express(self, a: f32, b: f32) -> f32 {
+        match self {
+            Dominance::Additive => (a + b) / 2.0,
+            Dominance::Dominant => a.max(b),
+            Dominance::Recessive => a.min(b),
+        }
+    }
+}
+
+/// The ways that a gene outside of its bounds can be brought back within them
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum BoundMode {
+    /// The gene is clamped to the nearest bound
+    Hard,
+    /// The gene is reflected back from the bound it exceeded by the amount that it exceeded it
+    Reflect,
+}
+
+impl BoundMode {
+    /// Returns the provided value brought back within the provided bounds
+    pub fn apply(self, value: f32, bounds: (f32, f32)) -> f32 {
+        let (min, max) = bounds;
+        if value >= min && value <= max {
+            return value;
+        }
+        match self {
+            BoundMode::Hard => value.max(min).min(max),
+            BoundMode::Reflect => {
+                let width = max - min;
+                if width <= 0.0 {
+                    return min;
+                }
+                // Reflecting back and forth between the bounds repeats every two widths
+                let offset = (value - min).rem_euclid(2.0 * width);
+                if offset <= width {
+                    min + offset
+                } else {
+                    max - (offset - width)
+                }
+            }
+        }
+    }
+}
+
+/// Testing that values outside of the bounds are clamped or reflected back within them
+#[test]
+fn test_bound_mode() {
+    assert_eq!(BoundMode::Hard.apply(-3.0, (0.0, 10.0)), 0.0);
+    assert_eq!(BoundMode::Hard.apply(12.0, (0.0, 10.0)), 10.0);
+    assert_eq!(BoundMode::Reflect.apply(-3.0, (0.0, 10.0)), 3.0);
+    assert_eq!(BoundMode::Reflect.apply(12.0, (0.0, 10.0)), 8.0);
+    // A value that overshoots by more than the width of the bounds reflects off both bounds
+    assert_eq!(BoundMode::Reflect.apply(23.0, (0.0, 10.0)), 3.0);
+    assert_eq!(BoundMode::Reflect.apply(5.0, (0.0, 10.0)), 5.0);
+}
+
+/// Testing that self-adapted mutation rates and step sizes stay within their bounds, and that
+/// the step size is only adapted when `adapt_step` is set
+#[test]
+fn test_self_adaptation() {
+    use rand::SeedableRng;
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut config = SelfAdaptationConfig {
+        initial_mutation_rate_range: (0.005, 0.05),
+        mutation_rate_bounds: (0.0001, 1.0),
+        adapt_step: true,
+        step_bounds: (0.01, 10.0),
+        learning_rate: 2.0,
+    };
+    let (mut mutation_rate, mut step) = (0.01, 1.0);
+    for _ in 0..1000 {
+        let adapted = config.adapt(mutation_rate, step, &mut rng);
+        mutation_rate = adapted.0;
+        step = adapted.1;
+        assert!(mutation_rate >= config.mutation_rate_bounds.0);
+        assert!(mutation_rate <= config.mutation_rate_bounds.1);
+        assert!(step >= config.step_bounds.0 && step <= config.step_bounds.1);
+    }
+
+    config.adapt_step = false;
+    assert_eq!(config.adapt(0.01, 3.5, &mut rng).1, 3.5);
+
+    // A step size of zero leaves genes unchanged, and mutated genes stay within their bounds
+    let gene = GeneConfig {
+        initial_range: (0.0, 1.0),
+        operator: MutationOperator::Uniform { amount: 0.5 },
+        bounds: (0.0, 1.0),
+        bound_mode: BoundMode::Hard,
+        dominance: Dominance::Additive,
+    };
+    assert_eq!(gene.mutate_with_step(0.3, 0.0, &mut rng), 0.3);
+    for _ in 0..100 {
+        let mutated = gene.mutate_with_step(0.9, 10.0, &mut rng);
+        assert!((0.0..=1.0).contains(&mutated));
+    }
+}
@@ -0,0 +1,98 @@
+//! A module for the errors that can stop the simulation from starting.
+
+use ggez::GameError;
+use std::{fmt, io, path::PathBuf};
+
+/// A problem with a single field of the configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigProblem {
+    /// The path of the field within the configuration, such as `fish.scale_range`
+    pub path: String,
+    /// What is wrong with the field
+    pub problem: String,
+    /// How the field could be fixed
+    pub suggestion: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}`: {} ({})", self.path, self.problem, self.suggestion)
+    }
+}
+
+/// The errors that can stop the simulation from starting
+#[derive(Debug)]
+pub enum Error {
+    /// A file could not be read or written
+    Io {
+        /// The path of the file
+        path: PathBuf,
+        /// Why the file could not be read or written
+        source: io::Error,
+    },
+    /// A file that the simulation needs doesn't exist
+    MissingFile {
+        /// The path that the file was expected at
+        path: PathBuf,
+        /// What the file is used for and how it could be provided
+        suggestion: String,
+    },
+    /// The configuration file is not valid RON, TOML or JSON, or doesn't match the structure of `Config`
+    Parse {
+        /// The path of the configuration file
+        path: PathBuf,
+        /// Where and why parsing failed
+        message: String,
+    },
+    /// An override of a configuration field could not be applied
+    Override {
+        /// The override as it was provided, such as `fish.mutation_rate=0.05` or
+        /// `EVOLUTION_FISH__MUTATION_RATE=0.05`
+        assignment: String,
+        /// Why the override could not be applied
+        message: String,
+    },
+    /// The configuration could not be serialized
+    Serialize(String),
+    /// The configuration was parsed, but some of its fields have invalid values
+    InvalidConfig(Vec<ConfigProblem>),
+    /// An error from `ggez` while setting up or running the window
+    Game(GameError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => {
+                write!(f, "Failed to access `{}`: {}", path.display(), source)
+            }
+            Error::MissingFile { path, suggestion } => {
+                write!(f, "Missing `{}`: {}", path.display(), suggestion)
+            }
+            Error::Parse { path, message } => {
+                write!(f, "Failed to load `{}`: {}", path.display(), message)
+            }
+            Error::Override {
+                assignment,
+                message,
+            } => write!(f, "Failed to apply override `{}`: {}", assignment, message),
+            Error::Serialize(message) => write!(f, "Failed to serialize the config: {}", message),
+            Error::InvalidConfig(problems) => {
+                write!(f, "The config has {} problem(s):", problems.len())?;
+                for problem in problems.iter() {
+                    write!(f, "\n  - {}", problem)?;
+                }
+                Ok(())
+            }
+            Error::Game(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<GameError> for Error {
+    fn from(error: GameError) -> Self {
+        Error::Game(error)
+    }
+}
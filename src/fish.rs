@@ -1,14 +1,25 @@
 //! A module for creating animated fish in a `ggez` window.
 use ggez::{
-    graphics,
-    graphics::{DrawParam, Rect, Color},
-    nalgebra::{distance, Point2, Vector2},
-    Context, GameResult,
+    graphics::{spritebatch::SpriteBatch, Color, DrawMode, DrawParam, MeshBuilder, Rect},
+    nalgebra::{Point2, Vector2},
+    GameResult,
+};
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+use std::{
+    f32::consts::PI,
+    sync::atomic::{AtomicU64, Ordering},
 };
-use rand::{Rng, rngs::ThreadRng};
-use serde::Deserialize;
 
-use super::{food::Food, inverse_map_range, Entity};
+use super::{
+    controller::{Brain, ControllerConfig, Senses},
+    dna::{BoundMode, GenesConfig, SelfAdaptationConfig, GENE_COUNT, GENE_NAMES},
+    food::Food,
+    inverse_map_range,
+    neat::Innovations,
+    world::{BoundaryMode, World},
+    Entity,
+};
 
 /// The indicies of each animation frame for the fish.
 ///
@@ -16,9 +27,26 @@ use super::{food::Food, inverse_map_range, Entity};
 /// animation frame to switch to next and looping back to the beginning of the array.
 const ANIMATION_FRAMES: [u8; 4] = [0, 1, 2, 1];
 
+/// The number of directions around a fish that are probed for walls to avoid
+const WALL_PROBES: usize = 8;
+
+/// The RGBA color of the debugging information related to food and prey
+const DEBUG_FOOD_COLOR: [f32; 4] = [0.0, 1.0, 0.0, 0.6];
+/// The RGBA color of the debugging information related to predators
+const DEBUG_PREDATOR_COLOR: [f32; 4] = [1.0, 0.2, 0.2, 0.6];
+/// The RGBA color of the debugging line of the velocity
+const DEBUG_VELOCITY_COLOR: [f32; 4] = [0.3, 0.6, 1.0, 0.8];
+/// The RGBA color of the debugging line to the target
+const DEBUG_TARGET_COLOR: [f32; 4] = [1.0, 1.0, 0.0, 0.6];
+
+/// The id that will be given to the next fish that is created
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
 /// The configuration structure specifically for fish that is read and deserialized from
-/// `config.ron`
-#[derive(Debug, Deserialize)]
+/// `config.ron`.
+/// Any field that is left out takes its value from `FishConfig::default()`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct FishConfig {
     /// The number of fish in the simulation
     pub quantity: usize,
@@ -26,6 +54,9 @@ pub struct FishConfig {
     pub eating_radius: f32,
     /// The frequency at which the fish's dna will mutate
     pub mutation_rate: f32,
+    /// If provided, each fish carries its own mutation rate (and optionally step size) which is
+    /// itself mutated on reproduction, rather than using `mutation_rate`
+    pub self_adaptation: Option<SelfAdaptationConfig>,
     /// The range of scales of the fish.
     /// E.g. A scale of 2 would result in a fish twice as large as the original image.
     pub scale_range: (f32, f32),
@@ -38,39 +69,160 @@ pub struct FishConfig {
     /// The number of frames in the simulation that will go by before going to the next
     /// animation frame at the fish's maximum speed.
     pub frames_per_animation_frame: f32,
+    /// The configuration of how each gene in the DNA is initialized and mutated
+    pub genes: GenesConfig,
+    /// Whether or not each fish carries two alleles of each gene, whose expressed value depends
+    /// on the gene's dominance. Offspring bred from two parents inherit one allele of each gene
+    /// from each parent.
+    pub diploid: bool,
+    /// How the fish decide how to steer
+    pub controller: ControllerConfig,
+    /// If provided, offspring are born as juveniles smaller than their adult scale and grow as
+    /// they eat, rather than being born at full size
+    pub growth: Option<GrowthConfig>,
+}
+
+impl Default for FishConfig {
+    fn default() -> Self {
+        Self {
+            quantity: 36,
+            eating_radius: 5.0,
+            mutation_rate: 0.01,
+            self_adaptation: None,
+            scale_range: (1.0, 2.0),
+            max_speed_range: (2.0, 5.0),
+            max_steering_force_range: (0.01, 0.1),
+            total_food_chain_links: 3,
+            frames_per_animation_frame: 2.0,
+            genes: GenesConfig::default(),
+            diploid: false,
+            controller: ControllerConfig::default(),
+            growth: None,
+        }
+    }
+}
+
+/// The configuration of how juvenile fish grow into adults.
+/// Any field that is left out takes its value from `GrowthConfig::default()`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GrowthConfig {
+    /// The scale of a newborn fish as a fraction of its adult scale
+    pub birth_scale: f32,
+    /// The scale that a juvenile grows by for each piece of food or fish that it eats, as a
+    /// fraction of its adult scale
+    pub growth_per_meal: f32,
+    /// How many times larger than a juvenile a fish has to be to eat it, when the juvenile is from
+    /// a higher link in the food chain that the fish otherwise couldn't eat
+    pub predation_scale_ratio: f32,
+    /// The largest fraction that an offspring's adult scale changes by when it mutates, which
+    /// happens at the mutation rate and is multiplied by the mutation step size.
+    /// The adult scale is kept within `FishConfig.scale_range`.
+    pub adult_scale_mutation: f32,
+}
+
+impl Default for GrowthConfig {
+    fn default() -> Self {
+        Self {
+            birth_scale: 0.4,
+            growth_per_meal: 0.1,
+            predation_scale_ratio: 1.5,
+            adult_scale_mutation: 0.1,
+        }
+    }
+}
+
+/// What an offspring inherits from its parents before it mutates
+struct Inheritance {
+    /// The DNA of the offspring
+    dna: [f32; GENE_COUNT],
+    /// The two alleles of each gene of the offspring, if it is diploid
+    alleles: Option<[[f32; GENE_COUNT]; 2]>,
+    /// The scale that the offspring grows towards
+    adult_scale: f32,
+}
+
+/// What a fish is currently heading towards to eat
+#[derive(Debug, Clone, Copy)]
+pub enum Target {
+    /// A piece of food at the provided position
+    Food(Point2<f32>),
+    /// A fish with the provided id from a lower link in the food chain, at the provided position
+    Prey(u64, Point2<f32>),
 }
 
 /// An entity that has the behavior of eating food and avoiding predators, along with basic physics.
 //#[derive(Clone)]
 pub struct Fish {
+    /// The unique id of the fish, which is used to keep track of it as other fish are born or die
+    id: u64,
+    /// The index of the fish's group, based on its level in the food chain
+    group_index: usize,
+    /// The id of the spawned fish that this fish descends from, which is its own id if it
+    /// was spawned rather than born
+    lineage: u64,
+    /// The id of the fish's species, which offspring inherit until the fish are next clustered
+    /// into species. `None` if speciation is disabled or the fish haven't been clustered yet.
+    species: Option<u64>,
+    /// The number of frames that the fish has been alive for
+    age: u32,
+    /// The number of offspring that the fish has had
+    children: u32,
+    /// The number of pieces of food that the fish has eaten
+    food_eaten: u32,
+    /// The number of fish that the fish has eaten
+    prey_eaten: u32,
+    /// What the fish was heading towards to eat during the last frame
+    target: Option<Target>,
+    /// The steering force towards food and prey applied during the last frame
+    food_steer: Vector2<f32>,
+    /// The steering force in relation to predators applied during the last frame
+    predator_steer: Vector2<f32>,
     /// The index of the current animation frame index stored in
     /// `ANIMATION_FRAMES`
     animation_index: usize,
-    /// The current frame number of the window to determine when to update the animation frame
-    /// specified from `FishConfig.frames_per_animation_frame`
+    /// The number of ticks since the animation frame last changed, to determine when to update
+    /// the animation frame specified from `FishConfig.frames_per_animation_frame`
     frame_index: u8,
     /// The DNA currently holds values for the weights of attraction and repulsion and the radii of perception
     /// for prey and predators respectively
-    dna: [f32; 4],
+    dna: [f32; GENE_COUNT],
+    /// The two alleles of each gene if the fish is diploid, in which case `dna` holds the values
+    /// that they express
+    alleles: Option<[[f32; GENE_COUNT]; 2]>,
+    /// The part of the fish's controller that evolves beyond its DNA, such as a neural network
+    brain: Brain,
+    /// The frequency at which the dna of this fish's offspring will mutate
+    mutation_rate: f32,
+    /// The multiplier applied to the step size of the mutation operators when the dna of this
+    /// fish's offspring mutates
+    mutation_step: f32,
     /// The rbg color of the fish
     color: (f32, f32, f32),
-    /// The health of the fish starts at 1 (full) and will decline by 0.001 per frame.
+    /// The health of the fish starts at 1 (full) and will decline by 0.001 per tick.
     /// A health of 0 or lower will result in an invisible fish.
     /// The opacity of a fish is dependant on its health.
     health: f32,
     /// The scale of the fish.
     /// E.g. A scale of 2 would result in a fish twice as large as the original image.
     scale: f32,
+    /// The scale that the fish grows towards as a juvenile, which is inherited from its parents
+    /// and mutates if fish grow. A fish whose scale is below this is a juvenile.
+    adult_scale: f32,
     /// The maximum velocity magnitude that the fish is able to reach
     max_speed: f32,
     /// The maximum steering/turning force that is able to be applied to the fish.
     max_steering_force: f32,
     /// The 2D position of the fish (the fish's location is in relation to its center)
     pos: Point2<f32>,
+    /// The position of the fish before the last tick, which drawing interpolates from
+    prev_pos: Point2<f32>,
     /// A radian angle that determines where the fish is pointed towards.
     /// An angle of zero would point the fish towards the right.
     /// This value is set to wherever the velocity vector is pointed towards.
     angle: f32,
+    /// The angle of the fish before the last tick, which drawing interpolates from
+    prev_angle: f32,
     /// The 2D velocity vector.
     vel: Vector2<f32>,
     /// The 2D acceleration vector.
@@ -82,8 +234,9 @@ impl Fish {
     pub fn new(
         fish_config: &FishConfig,
         group_index: &usize,
-        window_size: &(f32, f32),
-        rng: &mut ThreadRng,
+        world: &World,
+        innovations: &mut Innovations,
+        rng: &mut StdRng,
     ) -> Self {
         // Scale is a random field between the specified range in `FishConfig`
         let scale_range = (fish_config.scale_range.1 - fish_config.scale_range.0)
@@ -91,91 +244,322 @@ impl Fish {
         let min_scale = scale_range * *group_index as f32 + fish_config.scale_range.0;
         let max_scale = min_scale + scale_range;
         let scale = rng.gen_range(min_scale, max_scale);
-        // Max speed and max steering force are values that are inversely
-        // proportional to the scale value of the fish
-        let max_speed =
-            inverse_map_range(scale, fish_config.scale_range, fish_config.max_speed_range);
-        let max_steering_force = inverse_map_range(
-            scale,
-            fish_config.scale_range,
-            fish_config.max_steering_force_range,
-        );
+        let (max_speed, max_steering_force) = Self::abilities(scale, fish_config);
         // The angle is just a random radian around the unit circle
         let angle = rng.gen_range(0.0, 2.0 * std::f32::consts::PI);
-        // The position is a random location in the window
-        // TODO: In fullscreen mode, the window size may change on program
-        // execution resulting in the fish and food spawning in a different area than the
-        // window dimensions.
-        let pos = Point2::new(
-            rng.gen_range(0.0, window_size.0),
-            rng.gen_range(0.0, window_size.1),
-        );
-        // The DNA currently holds random values for the weights against steering
-        // towards food respectively.
-        let dna = [
-            // Food attraction weight
-            rng.gen_range(-2.0, 2.0),
-            // Predator attraction weight
-            rng.gen_range(-2.0, 2.0),
-            // Food perception radius
-            rng.gen_range(10.0, 100.0),
-            // Predator perception radius
-            rng.gen_range(10.0, 100.0),
-        ];
+        // The position is a random location in the world that isn't within a wall
+        let pos = world
+            .random_open_position(rng)
+            .unwrap_or_else(|| world.center());
+        // The DNA currently holds random values for the weights of attraction and the radii of
+        // perception for food and predators, within the initial ranges of `FishConfig.genes`
+        let alleles = if fish_config.diploid {
+            Some([
+                fish_config.genes.random_dna(rng),
+                fish_config.genes.random_dna(rng),
+            ])
+        } else {
+            None
+        };
+        let dna = match &alleles {
+            Some(alleles) => fish_config.genes.express(alleles),
+            None => fish_config.genes.random_dna(rng),
+        };
+        let brain = fish_config.controller.random_brain(innovations, rng);
+        // The mutation rate is either carried by each fish or the same for every fish
+        let mutation_rate = match &fish_config.self_adaptation {
+            Some(self_adaptation) => self_adaptation.random_mutation_rate(rng),
+            None => fish_config.mutation_rate,
+        };
         let color = (
             rng.gen_range(0.0, 1.0),
             rng.gen_range(0.0, 1.0),
             rng.gen_range(0.0, 1.0),
         );
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
         Self {
+            id,
+            group_index: *group_index,
+            lineage: id,
+            species: None,
+            age: 0,
+            children: 0,
+            food_eaten: 0,
+            prey_eaten: 0,
+            target: None,
+            food_steer: Vector2::new(0.0, 0.0),
+            predator_steer: Vector2::new(0.0, 0.0),
             animation_index: 0,
             frame_index: 0,
             scale,
+            adult_scale: scale,
             max_speed,
             max_steering_force,
             acc: Vector2::new(0.0, 0.0),
             vel: Vector2::new(0.0, 0.0),
             angle,
+            prev_angle: angle,
             pos,
+            prev_pos: pos,
             dna,
+            alleles,
+            brain,
+            mutation_rate,
+            mutation_step: 1.0,
             color,
             health: 1.0,
         }
     }
 
-    /// Creates a clone of a fish, with possible mutation(s) to the DNA
-    pub fn clone(&self, rng: &mut ThreadRng, mutation_rate: f32) -> Self {
-        // Possibly apply a mutation to genes in the cloned DNA, based on the `FishConfig.mutation_rate`
-        let mut dna = self.dna.clone();
-        for gene in dna.iter_mut() {
-            if rng.gen_range(0.0, 1.0) < mutation_rate {
-                *gene += rng.gen_range(-0.1, 0.1);
+    /// Creates a clone of a fish, with possible mutation(s) to the DNA, and counts it as one of
+    /// the fish's children
+    pub fn clone(
+        &mut self,
+        rng: &mut StdRng,
+        fish_config: &FishConfig,
+        innovations: &mut Innovations,
+    ) -> Self {
+        let brain = self
+            .brain
+            .offspring(None, &fish_config.controller, innovations, rng);
+        let inheritance = Inheritance {
+            dna: self.dna,
+            alleles: self.alleles,
+            adult_scale: self.adult_scale,
+        };
+        self.offspring(inheritance, brain, self.pos, rng, fish_config)
+    }
+
+    /// Creates an offspring of this fish and an optional mate from the same group, at a random
+    /// position in the world, and counts it as one of this fish's children.
+    /// Each of the offspring's genes is inherited from either parent with an equal chance before
+    /// possibly mutating, or for diploid fish, one random allele of each gene is inherited from
+    /// each parent. If fish grow, the adult scale is also inherited from either parent. Any part
+    /// of the brain that only one parent has is inherited from this fish, which should be the
+    /// fitter of the two.
+    pub fn breed(
+        &mut self,
+        mate: Option<&Fish>,
+        rng: &mut StdRng,
+        fish_config: &FishConfig,
+        world: &World,
+        innovations: &mut Innovations,
+    ) -> Self {
+        let mut dna = self.dna;
+        let mut alleles = self.alleles;
+        let mut adult_scale = self.adult_scale;
+        if let Some(mate) = mate {
+            match (&mut alleles, &mate.alleles) {
+                // Mendelian inheritance, where each parent passes on one of its two alleles
+                (Some(alleles), Some(mate_alleles)) => {
+                    for gene_index in 0..GENE_COUNT {
+                        let own_allele = alleles[rng.gen_range(0, 2)][gene_index];
+                        let mate_allele = mate_alleles[rng.gen_range(0, 2)][gene_index];
+                        alleles[0][gene_index] = own_allele;
+                        alleles[1][gene_index] = mate_allele;
+                    }
+                }
+                _ => {
+                    for (gene, mate_gene) in dna.iter_mut().zip(mate.dna.iter()) {
+                        if rng.gen_bool(0.5) {
+                            *gene = *mate_gene;
+                        }
+                    }
+                }
+            }
+            if fish_config.growth.is_some() && rng.gen_bool(0.5) {
+                adult_scale = mate.adult_scale;
+            }
+        }
+        let brain = self.brain.offspring(
+            mate.map(|mate| &mate.brain),
+            &fish_config.controller,
+            innovations,
+            rng,
+        );
+        let pos = world
+            .random_open_position(rng)
+            .unwrap_or_else(|| world.center());
+        let inheritance = Inheritance {
+            dna,
+            alleles,
+            adult_scale,
+        };
+        self.offspring(inheritance, brain, pos, rng, fish_config)
+    }
+
+    /// Creates an offspring of this fish with what it inherited and the provided brain at the
+    /// provided position, with possible mutation(s) to the DNA or to each allele, and to the
+    /// adult scale if fish grow, and counts it as one of the fish's children
+    fn offspring(
+        &mut self,
+        inheritance: Inheritance,
+        brain: Brain,
+        pos: Point2<f32>,
+        rng: &mut StdRng,
+        fish_config: &FishConfig,
+    ) -> Self {
+        self.children += 1;
+        let Inheritance {
+            mut dna,
+            mut alleles,
+            mut adult_scale,
+        } = inheritance;
+
+        // When self-adaptive, the mutation rate and step size are mutated first so that the
+        // offspring's own values are the ones applied to its DNA
+        let (mutation_rate, mutation_step) = match &fish_config.self_adaptation {
+            Some(self_adaptation) => {
+                self_adaptation.adapt(self.mutation_rate, self.mutation_step, rng)
+            }
+            None => (fish_config.mutation_rate, 1.0),
+        };
+
+        // Possibly apply a mutation to genes in the inherited DNA, based on the mutation rate and
+        // the mutation operator and bounds of each gene in `FishConfig.genes`. The alleles of a
+        // diploid fish each mutate independently and then determine its DNA.
+        let gene_configs = fish_config.genes.as_array();
+        let mutate = |genes: &mut [f32; GENE_COUNT], rng: &mut StdRng| {
+            for (gene, gene_config) in genes.iter_mut().zip(gene_configs.iter()) {
+                if rng.gen_range(0.0, 1.0) < mutation_rate {
+                    *gene = gene_config.mutate_with_step(*gene, mutation_step, rng);
+                }
+            }
+        };
+        match &mut alleles {
+            Some(alleles) => {
+                for allele_set in alleles.iter_mut() {
+                    mutate(allele_set, rng);
+                }
+                dna = fish_config.genes.express(alleles);
             }
+            None => mutate(&mut dna, rng),
         }
 
+        // Offspring are born as juveniles when fish grow, with a possibly mutated adult scale, or
+        // else at their full adult scale
+        let scale = match &fish_config.growth {
+            Some(growth) => {
+                if rng.gen_range(0.0, 1.0) < mutation_rate {
+                    let fraction = (growth.adult_scale_mutation * mutation_step).abs();
+                    if fraction > 0.0 {
+                        adult_scale *= 1.0 + rng.gen_range(-fraction, fraction);
+                    }
+                    adult_scale = BoundMode::Hard.apply(adult_scale, fish_config.scale_range);
+                }
+                adult_scale * growth.birth_scale
+            }
+            None => adult_scale,
+        };
+        let (max_speed, max_steering_force) = Self::abilities(scale, fish_config);
+
+        let angle = rng.gen_range(0.0, 2.0 * std::f32::consts::PI);
         Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            group_index: self.group_index,
+            lineage: self.lineage,
+            species: self.species,
+            age: 0,
+            children: 0,
+            food_eaten: 0,
+            prey_eaten: 0,
+            target: None,
+            food_steer: Vector2::new(0.0, 0.0),
+            predator_steer: Vector2::new(0.0, 0.0),
             animation_index: 0,
             frame_index: 0,
-            scale: self.scale,
-            max_speed: self.max_speed,
-            max_steering_force: self.max_steering_force,
+            scale,
+            adult_scale,
+            max_speed,
+            max_steering_force,
             acc: Vector2::new(0.0, 0.0),
             vel: Vector2::new(0.0, 0.0),
-            angle: rng.gen_range(0.0, 2.0 * std::f32::consts::PI),
-            pos: self.pos,
+            angle,
+            prev_angle: angle,
+            pos,
+            prev_pos: pos,
             dna,
+            alleles,
+            brain,
+            mutation_rate,
+            mutation_step,
             color: self.color,
             health: 1.0,
         }
     }
 
-    /// Draw the image that represents the fish, and animates it.
-    pub fn draw(
-        &mut self,
-        ctx: &mut Context,
-        image: &graphics::Image,
-        frames_per_animation_frame: f32,
-    ) -> GameResult {
+    /// Returns the maximum speed and maximum steering force of a fish of the provided scale,
+    /// which are inversely proportional to its scale within `FishConfig.scale_range`.
+    /// If fish grow, the range starts from the smallest newborn's scale instead, so that
+    /// juveniles slow down as they grow.
+    fn abilities(scale: f32, fish_config: &FishConfig) -> (f32, f32) {
+        let scale_range = match &fish_config.growth {
+            Some(growth) => (
+                growth.birth_scale * fish_config.scale_range.0,
+                fish_config.scale_range.1,
+            ),
+            None => fish_config.scale_range,
+        };
+        let scale = scale.clamp(scale_range.0, scale_range.1);
+        let max_speed = inverse_map_range(scale, scale_range, fish_config.max_speed_range);
+        let max_steering_force =
+            inverse_map_range(scale, scale_range, fish_config.max_steering_force_range);
+        (max_speed, max_steering_force)
+    }
+
+    /// Grows a juvenile fish towards its adult scale after it ate, if fish grow
+    fn grow(&mut self, fish_config: &FishConfig) {
+        if let Some(growth) = &fish_config.growth {
+            if self.is_juvenile() {
+                self.scale =
+                    (self.scale + growth.growth_per_meal * self.adult_scale).min(self.adult_scale);
+                let (max_speed, max_steering_force) = Self::abilities(self.scale, fish_config);
+                self.max_speed = max_speed;
+                self.max_steering_force = max_steering_force;
+            }
+        }
+    }
+
+    /// Returns whether or not this fish can eat the provided fish, which is either from a lower
+    /// link in the food chain, or a juvenile from a higher link that this fish is large enough
+    /// to eat
+    fn can_eat(&self, other: &Fish, fish_config: &FishConfig) -> bool {
+        if other.group_index < self.group_index {
+            return true;
+        }
+        match &fish_config.growth {
+            Some(growth) => {
+                other.group_index != self.group_index
+                    && other.is_juvenile()
+                    && self.scale >= other.scale * growth.predation_scale_ratio
+            }
+            None => false,
+        }
+    }
+
+    /// Brings the fish back to full health at a random position in the world, as if it had just
+    /// spawned, while keeping its DNA, appearance and id
+    pub fn respawn(&mut self, world: &World, rng: &mut StdRng) {
+        self.age = 0;
+        self.food_eaten = 0;
+        self.prey_eaten = 0;
+        self.target = None;
+        self.health = 1.0;
+        self.pos = world
+            .random_open_position(rng)
+            .unwrap_or_else(|| world.center());
+        self.prev_pos = self.pos;
+        self.vel = Vector2::new(0.0, 0.0);
+        self.acc = Vector2::new(0.0, 0.0);
+    }
+
+    /// Adds the image that represents the fish to the provided sprite batch of the fish
+    /// spritesheet.
+    /// The fish is drawn with the provided color, or its own color if none is provided, the
+    /// provided fraction of the way between its state before and after the last tick.
+    pub fn draw(&self, batch: &mut SpriteBatch, color: Option<Color>, alpha: f32, world: &World) {
+        let color =
+            color.unwrap_or_else(|| Color::new(self.color.0, self.color.1, self.color.2, 1.0));
         // Specifies what animation frame to display, and how to display it.
         let parameters = DrawParam {
             src: Rect {
@@ -184,33 +568,72 @@ impl Fish {
                 w: 1.0,
                 h: 1.0 / 3.0,
             },
-            dest: self.pos.into(),
-            rotation: self.angle,
+            dest: self.interpolated_pos(alpha, world).into(),
+            rotation: self.interpolated_angle(alpha),
             scale: Vector2::new(self.scale, self.scale).into(),
             offset: Point2::new(0.5, 0.5).into(),
-            color: Color::new(self.color.0, self.color.1, self.color.2, self.health),
+            color: Color::new(color.r, color.g, color.b, self.health),
         };
 
-        // Determines if it's time to update the animation frame, based on
-        // `FishConfig.frames_per_animation_frame`.
-        if self.frame_index as f32
-            >= frames_per_animation_frame * self.max_speed / self.vel.magnitude()
+        batch.add(parameters);
+    }
+
+    /// Adds the debugging information of the fish to the provided mesh builder:
+    /// its food and predator perception radii, the food and predator steering forces applied
+    /// during the last frame, its velocity, and a line to what it is heading towards to eat.
+    pub fn draw_debug(&self, builder: &mut MeshBuilder, world: &World) -> GameResult {
+        // The perception radii
+        for &(radius, color) in [
+            (self.dna[2], DEBUG_FOOD_COLOR),
+            (self.dna[3], DEBUG_PREDATOR_COLOR),
+        ]
+        .iter()
         {
-            self.frame_index = 0;
-            self.animation_index += 1;
-            self.animation_index %= 4;
-        // Doesn't increment the animation frame index if the fish isn't moving
-        } else if self.vel != Vector2::new(0.0, 0.0) {
-            self.frame_index += 1;
+            if radius > 0.0 {
+                builder.circle(DrawMode::stroke(1.0), self.pos, radius, 1.0, color.into());
+            }
+        }
+
+        // The steering forces are scaled so that the maximum steering force is as long as the
+        // fish's radius, and the velocity is scaled so that the maximum speed is twice as long
+        let steer_scale = self.radius() / self.max_steering_force;
+        let lines = [
+            (self.food_steer * steer_scale, DEBUG_FOOD_COLOR),
+            (self.predator_steer * steer_scale, DEBUG_PREDATOR_COLOR),
+            (
+                self.vel * 2.0 * self.radius() / self.max_speed,
+                DEBUG_VELOCITY_COLOR,
+            ),
+        ];
+        for &(vector, color) in lines.iter() {
+            if vector != Vector2::new(0.0, 0.0) {
+                builder.line(&[self.pos, self.pos + vector], 1.5, color.into())?;
+            }
         }
 
-        graphics::draw(ctx, image, parameters)?;
+        // The line to the target is drawn across the edges of a wrapping world
+        let target_pos = match self.target {
+            Some(Target::Food(pos)) | Some(Target::Prey(_, pos)) => Some(pos),
+            None => None,
+        };
+        if let Some(target_pos) = target_pos {
+            let offset = world.offset(self.pos, target_pos);
+            if offset != Vector2::new(0.0, 0.0) {
+                builder.line(
+                    &[self.pos, self.pos + offset],
+                    1.0,
+                    DEBUG_TARGET_COLOR.into(),
+                )?;
+            }
+        }
 
         Ok(())
     }
 
     /// Update the state of the fish in the simulation
-    pub fn update(&mut self) {
+    pub fn update(&mut self, frames_per_animation_frame: f32) {
+        self.prev_pos = self.pos;
+        self.prev_angle = self.angle;
         // Limit the velocity magnitude to the maximum speed.
         if self.vel.magnitude() > self.max_speed {
             self.vel = self.vel.normalize() * self.max_speed;
@@ -221,39 +644,132 @@ impl Fish {
         self.pos += self.vel;
         self.acc *= 0.0;
         self.health -= 0.001;
+        self.age += 1;
+
+        // Determines if it's time to update the animation frame, based on
+        // `FishConfig.frames_per_animation_frame`.
+        if self.frame_index as f32
+            >= frames_per_animation_frame * self.max_speed / self.vel.magnitude()
+        {
+            self.frame_index = 0;
+            self.animation_index += 1;
+            self.animation_index %= 4;
+        // Doesn't increment the animation frame index if the fish isn't moving
+        } else if self.vel != Vector2::new(0.0, 0.0) {
+            self.frame_index += 1;
+        }
+    }
+
+    /// Returns the position of the fish the provided fraction of the way from its position
+    /// before the last tick to its current position.
+    /// In `BoundaryMode::Wrap`, a fish that crossed an edge is moved across it rather than
+    /// through the whole world.
+    pub fn interpolated_pos(&self, alpha: f32, world: &World) -> Point2<f32> {
+        let pos = self.prev_pos + world.offset(self.prev_pos, self.pos) * alpha;
+        match world.boundary_mode {
+            BoundaryMode::Wrap => world.wrap(pos),
+            _ => pos,
+        }
+    }
+
+    /// Returns the angle of the fish the provided fraction of the way from its angle before the
+    /// last tick to its current angle, turning the shorter way around
+    fn interpolated_angle(&self, alpha: f32) -> f32 {
+        let turn = (self.angle - self.prev_angle + PI).rem_euclid(2.0 * PI) - PI;
+        self.prev_angle + turn * alpha
     }
 
     /// Applies the seeking behavior to the fish to eat prey and avoid predators.
+    /// `prey` holds the groups below this fish's in the food chain, and `larger` holds the groups
+    /// above it, whose juveniles may be eaten if fish grow.
+    /// Returns the fish that this fish ate, if it caught one.
     pub fn behave(
         &mut self,
         food: &mut Vec<Food>,
         prey: &mut [Vec<Self>],
+        larger: &mut [Vec<Self>],
         predator_positions: &Option<Vec<Point2<f32>>>,
-        eating_radius: f32,
-    ) {
+        fish_config: &FishConfig,
+        world: &World,
+    ) -> Option<Self> {
         // Obtains the steering forces based on the nearest prey and predator that exist
         // within the respective perceptions (`self.dna[2]` and `self.dna[3]`)
         //
         // Then applies the weights of attraction for prey and predators respectively (`self.dna[0]` and `self.dna[1]`)
-        let food_steer = self.eat(food, prey, eating_radius);
+        let (food_steer, eaten) = self.eat(food, prey, larger, fish_config, world);
         let predator_steer = match predator_positions {
-            Some(predator_positions) => self.avoid(predator_positions),
+            Some(predator_positions) => self.avoid(predator_positions, world),
             None => Vector2::new(0.0, 0.0),
         };
+        let wall_steer = self.avoid_walls(world);
 
-        // Applying the steering forces
-        self.acc += food_steer + predator_steer;
+        // A fish with a network controller steers however its network decides from what it
+        // senses, while still steering away from walls
+        let senses = self.senses(predator_positions, world);
+        match self.brain.steer(&senses) {
+            Some(steer) => {
+                let mut steer = steer * self.max_steering_force;
+                if steer.magnitude() > self.max_steering_force {
+                    steer = steer.normalize() * self.max_steering_force;
+                }
+                self.food_steer = steer;
+                self.predator_steer = Vector2::new(0.0, 0.0);
+                self.acc += steer + wall_steer;
+            }
+            None => {
+                self.food_steer = food_steer;
+                self.predator_steer = predator_steer;
+
+                // Applying the steering forces
+                self.acc += food_steer + predator_steer + wall_steer;
+            }
+        }
+        eaten
+    }
+
+    /// Returns what the fish senses for its controller: its target, the nearest predator within
+    /// its predator perception, its velocity and its health
+    fn senses(&self, predator_positions: &Option<Vec<Point2<f32>>>, world: &World) -> Senses {
+        let target = match self.target {
+            Some(Target::Food(pos)) | Some(Target::Prey(_, pos)) => {
+                Some(world.offset(self.pos, pos) / self.dna[2].max(1.0))
+            }
+            None => None,
+        };
+        let predator = predator_positions
+            .as_ref()
+            .and_then(|predator_positions| self.nearest_predator(predator_positions, world))
+            .filter(|(_, distance)| *distance <= self.dna[3])
+            .map(|(predator_pos, _)| world.offset(self.pos, predator_pos) / self.dna[3].max(1.0));
+        Senses {
+            target,
+            predator,
+            velocity: self.vel / self.max_speed.max(f32::EPSILON),
+            health: self.health,
+        }
     }
 
     /// Determine the closest `Entity` in food and prey, and what the steering force should be applied to the
     /// `Fish` to head towards that `Entity`.
-    /// Returns a the steering force of atraction for the `Entity`
+    /// Fish in `larger` are only edible if they are juveniles that this fish can eat.
+    /// Returns a the steering force of atraction for the `Entity`, along with the fish that was
+    /// eaten if the `Entity` was prey within reach
     pub fn eat(
         &mut self,
         food: &mut Vec<Food>,
         prey: &mut [Vec<Self>],
-        eating_radius: f32,
-    ) -> Vector2<f32> {
+        larger: &mut [Vec<Self>],
+        fish_config: &FishConfig,
+        world: &World,
+    ) -> (Vector2<f32>, Option<Self>) {
+        let eating_radius = fish_config.eating_radius;
+        // Juveniles of larger fish groups are only considered if fish grow
+        let larger: &mut [Vec<Self>] = match fish_config.growth {
+            Some(_) => larger,
+            None => &mut [],
+        };
+        let mut prey: Vec<&mut Vec<Self>> = prey.iter_mut().chain(larger.iter_mut()).collect();
+
         // The record distance of closest edible entity
         // The intial value of this variable is not considered.
         let mut record = 0.0;
@@ -261,16 +777,23 @@ impl Fish {
         let mut closest = None;
         // Find the nearest edible entity
         for (entity_index, entity) in food.iter().enumerate() {
-            let distance = distance(&entity.pos(), &self.pos);
-            if closest.is_none() || distance < record {
+            let distance = world.distance(entity.pos(), self.pos);
+            if (closest.is_none() || distance < record)
+                && world.line_of_sight(self.pos, entity.pos())
+            {
                 record = distance;
                 closest = Some((None, entity_index));
             }
         }
         for (group_index, prey_group) in prey.iter().enumerate() {
             for (entity_index, entity) in prey_group.iter().enumerate() {
-                let distance = distance(&entity.pos(), &self.pos);
-                if closest.is_none() || (distance < record && distance <= self.dna[2]) {
+                if !self.can_eat(entity, fish_config) {
+                    continue;
+                }
+                let distance = world.distance(entity.pos(), self.pos);
+                if (closest.is_none() || (distance < record && distance <= self.dna[2]))
+                    && world.line_of_sight(self.pos, entity.pos())
+                {
                     record = distance;
                     closest = Some((Some(group_index), entity_index));
                 }
@@ -278,46 +801,86 @@ impl Fish {
         }
 
         if let Some((group_index, entity_index)) = closest {
+            self.target = Some(match group_index {
+                Some(group_index) => {
+                    let prey = &prey[group_index][entity_index];
+                    Target::Prey(prey.id, prey.pos)
+                }
+                None => Target::Food(food[entity_index].pos),
+            });
             match group_index {
                 Some(group_index) => {
-                    return self.consume(
-                        &mut prey[group_index],
+                    let (steer_force, eaten) = self.consume(
+                        prey[group_index],
                         entity_index,
                         record,
                         eating_radius,
-                    )
+                        world,
+                    );
+                    if eaten.is_some() {
+                        self.prey_eaten += 1;
+                        self.grow(fish_config);
+                    }
+                    return (steer_force, eaten);
+                }
+                None => {
+                    let (steer_force, eaten) =
+                        self.consume(food, entity_index, record, eating_radius, world);
+                    if eaten.is_some() {
+                        self.food_eaten += 1;
+                        self.grow(fish_config);
+                    }
+                    return (steer_force, None);
                 }
-                None => return self.consume(food, entity_index, record, eating_radius),
             };
         }
 
         // If there was nothing edible nearby, the resulting steering force will be nothing.
-        Vector2::new(0.0, 0.0)
+        self.target = None;
+        (Vector2::new(0.0, 0.0), None)
     }
 
     /// Returns the steering force to head towards the provided entity.
     /// If this fish's radius is overlapping the provided entity's radius, then remove it
-    /// from its collection.
+    /// from its collection and return it.
     fn consume<E: Entity>(
         &mut self,
         entities: &mut Vec<E>,
         entity_index: usize,
         record: f32,
         eating_radius: f32,
-    ) -> Vector2<f32> {
-        let steer_force = self.seek(entities[entity_index].pos()) * self.dna[0];
+        world: &World,
+    ) -> (Vector2<f32>, Option<E>) {
+        let steer_force = self.seek(entities[entity_index].pos(), world) * self.dna[0];
         if record <= entities[entity_index].radius() + eating_radius {
             if self.health < 1.0 {
                 self.health += 0.01;
             }
-            entities.remove(entity_index);
+            return (steer_force, Some(entities.remove(entity_index)));
         }
-        steer_force
+        (steer_force, None)
     }
 
     /// Determine the closest predator, and what the steering force should be applied to the
     /// `Fish` to avoid that predator
-    pub fn avoid(&mut self, predator_positions: &Vec<Point2<f32>>) -> Vector2<f32> {
+    pub fn avoid(&mut self, predator_positions: &[Point2<f32>], world: &World) -> Vector2<f32> {
+        if let Some((closest_predator, record)) = self.nearest_predator(predator_positions, world) {
+            // Determines if the predator is perceived
+            if record <= self.dna[3] {
+                return self.seek(closest_predator, world) * self.dna[1];
+            }
+        }
+
+        // If there was no predator nearby, the resulting steering force will be nothing.
+        Vector2::new(0.0, 0.0)
+    }
+
+    /// Returns the position of and distance to the nearest predator in sight, if any
+    fn nearest_predator(
+        &self,
+        predator_positions: &[Point2<f32>],
+        world: &World,
+    ) -> Option<(Point2<f32>, f32)> {
         // The record distance of closest predator
         // The intial value of this variable is not considered.
         let mut record = 0.0;
@@ -325,53 +888,100 @@ impl Fish {
         let mut closest = None;
         // Find the nearest predator
         for (i, predator_position) in predator_positions.iter().enumerate() {
-            let distance = distance(predator_position, &self.pos);
-            if closest.is_none() || distance < record {
+            let distance = world.distance(*predator_position, self.pos);
+            if (closest.is_none() || distance < record)
+                && world.line_of_sight(self.pos, *predator_position)
+            {
                 record = distance;
                 closest = Some(i);
             }
         }
+        closest.map(|closest_index| (predator_positions[closest_index], record))
+    }
 
-        if let Some(closest_index) = closest {
-            let closest_predator = &predator_positions[closest_index];
+    /// Returns a steering force away from any walls of the arena within
+    /// `ArenaConfig.wall_avoidance_distance` of the fish
+    pub fn avoid_walls(&mut self, world: &World) -> Vector2<f32> {
+        let avoidance_distance = world.arena.wall_avoidance_distance;
+        if !world.arena.has_walls() || avoidance_distance <= 0.0 {
+            return Vector2::new(0.0, 0.0);
+        }
 
-            // Determines if the predator is perceived
-            if record <= self.dna[3] {
-                return self.seek(*closest_predator) * self.dna[1];
+        // Probe for walls in evenly spaced directions around the fish, and head away from the
+        // directions that contain a wall
+        let mut away = Vector2::new(0.0, 0.0);
+        for probe_index in 0..WALL_PROBES {
+            let angle =
+                self.angle + probe_index as f32 * 2.0 * std::f32::consts::PI / WALL_PROBES as f32;
+            let direction = Vector2::new(angle.cos(), angle.sin());
+            if world.is_wall(self.pos + direction * avoidance_distance) {
+                away -= direction;
             }
         }
 
-        // If there was no predator nearby, the resulting steering force will be nothing.
-        Vector2::new(0.0, 0.0)
+        if away == Vector2::new(0.0, 0.0) {
+            return away;
+        }
+        self.seek(self.pos + away, world)
     }
 
-    /// Bounds the fish to swim within the window based on the provided padding
+    /// Bounds the fish to swim within the world based on its boundary mode and padding
     /// thickness.
-    pub fn bound(&mut self, window_size: &(f32, f32), boundary_padding: f32) {
-        let out_of_bounds = if self.pos.x < boundary_padding {
-            true
-        } else if self.pos.x > window_size.0 - boundary_padding {
-            true
-        } else if self.pos.y < boundary_padding {
-            true
-        } else if self.pos.y > window_size.1 - boundary_padding {
-            true
-        } else {
-            false
-        };
+    pub fn bound(&mut self, world: &World) {
+        let (width, height) = world.size;
+        let padding = world.boundary_padding;
 
-        if out_of_bounds {
-            // The steering force needed to head towards the center of the window
-            let center_steer = self.seek(Point2::new(window_size.0 / 2.0, window_size.1 / 2.0));
+        match world.boundary_mode {
+            BoundaryMode::Steer => {
+                let out_of_bounds = self.pos.x < padding
+                    || self.pos.x > width - padding
+                    || self.pos.y < padding
+                    || self.pos.y > height - padding;
+
+                if out_of_bounds {
+                    // The steering force needed to head towards the center of the world
+                    let center_steer = self.seek(world.center(), world);
+
+                    self.acc += center_steer;
+                }
+            }
+            BoundaryMode::Wrap => self.pos = world.wrap(self.pos),
+            BoundaryMode::Reflect => {
+                // The fish is mirrored back inside of any wall it passed through, and its
+                // velocity is pointed away from that wall
+                if self.pos.x < padding {
+                    self.pos.x = 2.0 * padding - self.pos.x;
+                    self.vel.x = self.vel.x.abs();
+                } else if self.pos.x > width - padding {
+                    self.pos.x = 2.0 * (width - padding) - self.pos.x;
+                    self.vel.x = -self.vel.x.abs();
+                }
+                if self.pos.y < padding {
+                    self.pos.y = 2.0 * padding - self.pos.y;
+                    self.vel.y = self.vel.y.abs();
+                } else if self.pos.y > height - padding {
+                    self.pos.y = 2.0 * (height - padding) - self.pos.y;
+                    self.vel.y = -self.vel.y.abs();
+                }
+                self.angle = self.vel.y.atan2(self.vel.x);
+            }
+        }
 
-            self.acc += center_steer;
+        // A fish that swam into a wall of the arena is moved back out of it and turned around
+        if world.is_wall(self.pos) {
+            self.pos -= self.vel;
+            if world.boundary_mode == BoundaryMode::Wrap {
+                self.pos = world.wrap(self.pos);
+            }
+            self.vel = -self.vel;
+            self.angle = self.vel.y.atan2(self.vel.x);
         }
     }
 
     /// Returns a force that will point the fish towards its target.
-    pub fn seek(&mut self, target: Point2<f32>) -> Vector2<f32> {
+    pub fn seek(&mut self, target: Point2<f32>, world: &World) -> Vector2<f32> {
         // Get the desired velocity vector.
-        let mut desired = target - self.pos;
+        let mut desired = world.offset(self.pos, target);
         // Set the magnitude of the desired vector to the maximum speed.
         desired = desired.normalize() * self.max_speed;
 
@@ -384,6 +994,138 @@ impl Fish {
         steering_force
     }
 
+    /// Returns the unique id of this fish
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the id of the spawned fish that this fish descends from
+    pub fn lineage(&self) -> u64 {
+        self.lineage
+    }
+
+    /// Returns the index of this fish's group, based on its level in the food chain
+    pub fn group_index(&self) -> usize {
+        self.group_index
+    }
+
+    /// Returns the id of this fish's species, if the fish have been clustered into species
+    pub fn species(&self) -> Option<u64> {
+        self.species
+    }
+
+    /// Sets the id of this fish's species
+    pub fn set_species(&mut self, species: u64) {
+        self.species = Some(species);
+    }
+
+    /// Returns whether or not this fish is a juvenile that is still growing towards its adult
+    /// scale
+    pub fn is_juvenile(&self) -> bool {
+        self.scale < self.adult_scale
+    }
+
+    /// Returns the health of this fish
+    pub fn health(&self) -> f32 {
+        self.health
+    }
+
+    /// Returns the number of frames that this fish has been alive for
+    pub fn age(&self) -> u32 {
+        self.age
+    }
+
+    /// Returns the number of pieces of food that this fish has eaten
+    pub fn food_eaten(&self) -> u32 {
+        self.food_eaten
+    }
+
+    /// Returns the number of fish that this fish has eaten
+    pub fn prey_eaten(&self) -> u32 {
+        self.prey_eaten
+    }
+
+    /// Returns what this fish was heading towards to eat during the last frame
+    pub fn target(&self) -> Option<Target> {
+        self.target
+    }
+
+    /// Returns the lines of text describing this fish, to be displayed when it is inspected
+    pub fn inspect_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("Fish #{}", self.id),
+            format!("Group: {}", self.group_index),
+        ];
+        if let Some(species) = self.species {
+            lines.push(format!("Species: #{}", species));
+        }
+        for (gene_index, (name, gene)) in GENE_NAMES.iter().zip(self.dna.iter()).enumerate() {
+            lines.push(match &self.alleles {
+                Some(alleles) => format!(
+                    "{}: {:.3} ({:.3} / {:.3})",
+                    name, gene, alleles[0][gene_index], alleles[1][gene_index]
+                ),
+                None => format!("{}: {:.3}", name, gene),
+            });
+        }
+        lines.extend(self.brain.inspect_lines());
+        if self.is_juvenile() {
+            lines.push(format!(
+                "Scale: {:.3} (juvenile of {:.3})",
+                self.scale, self.adult_scale
+            ));
+        } else {
+            lines.push(format!("Scale: {:.3}", self.scale));
+        }
+        lines.push(format!("Mutation rate: {:.4}", self.mutation_rate));
+        lines.push(format!("Mutation step: {:.3}", self.mutation_step));
+        lines.push(format!("Health: {:.3}", self.health));
+        lines.push(format!("Age: {}", self.age));
+        lines.push(format!(
+            "Speed: {:.2} / {:.2}",
+            self.vel.magnitude(),
+            self.max_speed
+        ));
+        lines.push(format!("Children: {}", self.children));
+        lines.push(format!(
+            "Eaten: {} food, {} fish",
+            self.food_eaten, self.prey_eaten
+        ));
+        lines.push(match self.target {
+            Some(Target::Food(pos)) => format!("Target: food at ({:.0}, {:.0})", pos.x, pos.y),
+            Some(Target::Prey(id, pos)) => {
+                format!("Target: fish #{} at ({:.0}, {:.0})", id, pos.x, pos.y)
+            }
+            None => "Target: none".to_string(),
+        });
+        lines
+    }
+
+    /// Returns the DNA of this fish
+    pub fn dna(&self) -> &[f32; GENE_COUNT] {
+        &self.dna
+    }
+
+    /// Returns the two alleles of each gene of this fish, if it is diploid
+    pub fn alleles(&self) -> Option<&[[f32; GENE_COUNT]; 2]> {
+        self.alleles.as_ref()
+    }
+
+    /// Returns the part of this fish's controller that evolves beyond its DNA
+    pub fn brain(&self) -> &Brain {
+        &self.brain
+    }
+
+    /// Returns the frequency at which the dna of this fish's offspring will mutate
+    pub fn mutation_rate(&self) -> f32 {
+        self.mutation_rate
+    }
+
+    /// Returns the multiplier applied to the mutation step size of this fish's offspring
+    pub fn mutation_step(&self) -> f32 {
+        self.mutation_step
+    }
+
     /// Returns whether or not this fish is alive
     pub fn is_alive(&self) -> bool {
         self.health >= 0.0
@@ -400,3 +1142,129 @@ impl Entity for Fish {
         self.scale * 12.0
     }
 }
+
+/// Testing that juveniles are born small, grow faster-moving into slower adults as they eat, and
+/// can be eaten by larger fish of lower groups until they are grown
+#[test]
+fn test_growth() {
+    use super::arena::Arena;
+    use rand::SeedableRng;
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut innovations = Innovations::default();
+    let world = World::new((100.0, 50.0), 0.0, BoundaryMode::Wrap, Arena::default());
+    let fish_config = FishConfig {
+        scale_range: (0.5, 2.0),
+        growth: Some(GrowthConfig {
+            birth_scale: 0.25,
+            growth_per_meal: 0.5,
+            predation_scale_ratio: 1.5,
+            adult_scale_mutation: 0.0,
+        }),
+        ..FishConfig::default()
+    };
+    let prey = Fish::new(&fish_config, &0, &world, &mut innovations, &mut rng);
+    let mut predator = Fish::new(&fish_config, &2, &world, &mut innovations, &mut rng);
+    let mut juvenile = predator.clone(&mut rng, &fish_config, &mut innovations);
+    assert!(juvenile.is_juvenile());
+    assert_eq!(juvenile.scale, predator.scale * 0.25);
+    assert!(juvenile.max_speed > predator.max_speed);
+    assert!(prey.can_eat(&juvenile, &fish_config));
+    assert!(!prey.can_eat(&predator, &fish_config));
+
+    // Juveniles slow down with every meal as they grow
+    let mut max_speed = juvenile.max_speed;
+    for _ in 0..2 {
+        juvenile.grow(&fish_config);
+        assert!(juvenile.max_speed < max_speed);
+        max_speed = juvenile.max_speed;
+    }
+    assert!(!juvenile.is_juvenile());
+    assert_eq!(juvenile.scale, predator.scale);
+    assert_eq!(juvenile.max_speed, predator.max_speed);
+    assert!(!prey.can_eat(&juvenile, &fish_config));
+
+    // The adult scale is heritable, mutating within the scale range
+    let fish_config = FishConfig {
+        mutation_rate: 1.0,
+        growth: Some(GrowthConfig {
+            adult_scale_mutation: 0.5,
+            ..GrowthConfig::default()
+        }),
+        ..fish_config
+    };
+    let mut adult_scales = Vec::new();
+    for _ in 0..50 {
+        let offspring = predator.clone(&mut rng, &fish_config, &mut innovations);
+        assert!(offspring.adult_scale >= 0.5 && offspring.adult_scale <= 2.0);
+        assert_eq!(offspring.scale, offspring.adult_scale * 0.4);
+        adult_scales.push(offspring.adult_scale);
+    }
+    assert!(adult_scales
+        .iter()
+        .any(|&scale| scale != predator.adult_scale));
+    let mut descendant = predator.clone(&mut rng, &fish_config, &mut innovations);
+    for _ in 0..20 {
+        descendant = descendant.clone(&mut rng, &fish_config, &mut innovations);
+    }
+    assert_ne!(descendant.adult_scale, predator.adult_scale);
+}
+
+/// Testing that diploid offspring inherit one allele of each gene from each parent and express
+/// them through the dominance of each gene
+#[test]
+fn test_mendelian_inheritance() {
+    use super::arena::Arena;
+    use rand::SeedableRng;
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let world = World::new((100.0, 50.0), 0.0, BoundaryMode::Wrap, Arena::default());
+    let fish_config = FishConfig {
+        diploid: true,
+        mutation_rate: 0.0,
+        self_adaptation: None,
+        ..Default::default()
+    };
+    // Homozygous parents, whose alleles are all drawn from either the high or the low half of
+    // each gene's initial range
+    let homozygous = |high: bool, rng: &mut StdRng| {
+        let mut config = fish_config.clone();
+        let genes = &mut config.genes;
+        for gene in [
+            &mut genes.food_weight,
+            &mut genes.predator_weight,
+            &mut genes.food_perception,
+            &mut genes.predator_perception,
+        ]
+        .iter_mut()
+        {
+            let midpoint = gene.allele_midpoint();
+            gene.initial_range = if high {
+                (midpoint, gene.initial_range.1)
+            } else {
+                (gene.initial_range.0, midpoint)
+            };
+        }
+        Fish::new(&config, &0, &world, &mut Innovations::default(), rng)
+    };
+    let mut parent = homozygous(true, &mut rng);
+    let mate = homozygous(false, &mut rng);
+    let child = parent.breed(
+        Some(&mate),
+        &mut rng,
+        &fish_config,
+        &world,
+        &mut Innovations::default(),
+    );
+
+    let (parent_alleles, mate_alleles) = (parent.alleles().unwrap(), mate.alleles().unwrap());
+    let child_alleles = child.alleles().unwrap();
+    for gene_index in 0..GENE_COUNT {
+        let from = |alleles: &[[f32; GENE_COUNT]; 2], allele: f32| {
+            alleles.iter().any(|set| set[gene_index] == allele)
+        };
+        assert!(from(parent_alleles, child_alleles[0][gene_index]));
+        assert!(from(mate_alleles, child_alleles[1][gene_index]));
+    }
+    assert_eq!(*child.dna(), fish_config.genes.express(child_alleles));
+}
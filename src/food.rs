@@ -1,18 +1,35 @@
 //! A module for creating circles in a `ggez` window.
 
-use ggez::{graphics, nalgebra::Point2, Context, GameResult};
-use serde::Deserialize;
+use ggez::{
+    graphics::{DrawMode, MeshBuilder},
+    nalgebra::Point2,
+};
+use serde::{Deserialize, Serialize};
 
 use super::Entity;
 
 /// The configuration structure specifically for food that is read and deserialized from
-/// `config.ron`
-#[derive(Debug, Deserialize)]
+/// `config.ron`.
+/// Any field that is left out takes its value from `FoodConfig::default()`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct FoodConfig {
     /// The amount of food in the simulation
     pub quantity: u32,
     /// The range of radii of the food.
     pub radius_range: (f32, f32),
+    /// The chance of a piece of food spawning each tick
+    pub spawn_rate: f32,
+}
+
+impl Default for FoodConfig {
+    fn default() -> Self {
+        Self {
+            quantity: 200,
+            radius_range: (5.0, 20.0),
+            spawn_rate: 0.1,
+        }
+    }
 }
 
 /// An edible entity for fish
@@ -41,19 +58,16 @@ impl Food {
         }
     }
 
-    /// Draws the circle representing the piece of food in the `ggez` window
-    pub fn draw(&self, ctx: &mut Context) -> GameResult {
-        let circle = graphics::Mesh::new_circle(
-            ctx,
-            graphics::DrawMode::fill(),
-            Point2::new(0.0, 0.0),
+    /// Adds the circle representing the piece of food to the provided mesh builder, so that
+    /// every piece of food can be drawn at once
+    pub fn draw(&self, builder: &mut MeshBuilder) {
+        builder.circle(
+            DrawMode::fill(),
+            self.pos,
             self.radius,
             1.0,
             self.color.into(),
-        )?;
-
-        graphics::draw(ctx, &circle, (self.pos,))?;
-        Ok(())
+        );
     }
 }
 
@@ -0,0 +1,227 @@
+//! A module for the classic generational genetic algorithm, where a population is evaluated for
+//! a fixed number of ticks and then replaced by the offspring of its fittest fish.
+
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    controller::ControllerConfig,
+    fish::{Fish, FishConfig},
+    neat::Innovations,
+    world::World,
+};
+
+/// The configuration of the generational mode that is read and deserialized from `config.ron`.
+/// Any field that is left out takes its value from `GenerationalConfig::default()`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GenerationalConfig {
+    /// The number of ticks that each generation is evaluated for
+    pub generation_ticks: u64,
+    /// How each fish is scored at the end of its generation
+    pub fitness: FitnessConfig,
+    /// How the parents of each offspring are chosen
+    pub selection: Selection,
+    /// The chance of an offspring's genes being crossed over from two parents, rather than
+    /// copied from one
+    pub crossover_rate: f32,
+    /// The number of the fittest fish of each group that are carried over unchanged into the
+    /// next generation
+    pub elites: usize,
+}
+
+impl Default for GenerationalConfig {
+    fn default() -> Self {
+        Self {
+            generation_ticks: 1500,
+            fitness: FitnessConfig::default(),
+            selection: Selection::Tournament { size: 3 },
+            crossover_rate: 0.7,
+            elites: 1,
+        }
+    }
+}
+
+/// The weights of what a fish is scored on at the end of its generation.
+/// Any weight that is left out takes its value from `FitnessConfig::default()`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FitnessConfig {
+    /// The score for each tick that the fish survived
+    pub survival: f32,
+    /// The score for each piece of food that the fish ate
+    pub food_eaten: f32,
+    /// The score for each fish that the fish ate
+    pub prey_eaten: f32,
+}
+
+impl Default for FitnessConfig {
+    fn default() -> Self {
+        Self {
+            survival: 0.01,
+            food_eaten: 1.0,
+            prey_eaten: 2.0,
+        }
+    }
+}
+
+impl FitnessConfig {
+    /// Returns the fitness of the provided fish
+    pub fn score(&self, fish: &Fish) -> f32 {
+        self.survival * fish.age() as f32
+            + self.food_eaten * fish.food_eaten() as f32
+            + self.prey_eaten * fish.prey_eaten() as f32
+    }
+}
+
+/// The ways that the parents of each offspring can be chosen
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum Selection {
+    /// The fittest of `size` randomly chosen fish
+    Tournament { size: usize },
+    /// A random fish, with a chance proportional to how much fitter it is than the least fit
+    Roulette,
+    /// A random fish from the fittest `fraction` of the group.
+    /// E.g. A fraction of 0.2 only breeds the fittest 20% of the fish.
+    Elitist { fraction: f32 },
+}
+
+impl Selection {
+    /// Returns the index of a parent within the provided fitnesses, which are sorted from the
+    /// fittest to the least fit
+    fn select(self, fitnesses: &[f32], rng: &mut StdRng) -> usize {
+        match self {
+            Selection::Tournament { size } => (0..size.max(1))
+                .map(|_| rng.gen_range(0, fitnesses.len()))
+                .min()
+                .unwrap_or(0),
+            Selection::Roulette => {
+                let least_fit = fitnesses[fitnesses.len() - 1];
+                let total: f32 = fitnesses.iter().map(|fitness| fitness - least_fit).sum();
+                // Every fish is equally likely to be chosen when they are all as fit, or when
+                // their fitnesses can't be summed
+                if !(total > 0.0 && total.is_finite()) {
+                    return rng.gen_range(0, fitnesses.len());
+                }
+                let mut spin = rng.gen_range(0.0, total);
+                for (index, fitness) in fitnesses.iter().enumerate() {
+                    spin -= fitness - least_fit;
+                    if spin < 0.0 {
+                        return index;
+                    }
+                }
+                0
+            }
+            Selection::Elitist { fraction } => {
+                let count = (fitnesses.len() as f32 * fraction).ceil() as usize;
+                rng.gen_range(0, count.max(1).min(fitnesses.len()))
+            }
+        }
+    }
+}
+
+/// Returns the next generation of a group of fish, with as many fish as the provided
+/// population, which holds every fish of the group that lived during the last generation.
+/// If `within_species` is set, each offspring's parents are of the same species.
+/// With a NEAT controller that shares fitness, each fish's fitness is divided by the number of
+/// fish in its species.
+pub fn next_generation(
+    population: Vec<Fish>,
+    config: &GenerationalConfig,
+    fish_config: &FishConfig,
+    within_species: bool,
+    world: &World,
+    innovations: &mut Innovations,
+    rng: &mut StdRng,
+) -> Vec<Fish> {
+    if population.is_empty() {
+        return population;
+    }
+
+    let fitness_sharing = match &fish_config.controller {
+        ControllerConfig::Neat(neat_config) => neat_config.fitness_sharing,
+        _ => false,
+    };
+    let fitnesses: Vec<f32> = population
+        .iter()
+        .map(|fish| {
+            let fitness = config.fitness.score(fish);
+            if fitness_sharing {
+                let species_size = population
+                    .iter()
+                    .filter(|other| other.species() == fish.species())
+                    .count();
+                fitness / species_size as f32
+            } else {
+                fitness
+            }
+        })
+        .collect();
+
+    // The fittest fish are first, so that selection can choose by rank
+    let mut scored: Vec<(f32, Fish)> = fitnesses.into_iter().zip(population).collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let (fitnesses, mut population): (Vec<f32>, Vec<Fish>) = scored.into_iter().unzip();
+
+    let elites = config.elites.min(population.len());
+    let mut offspring = Vec::with_capacity(population.len());
+    for _ in elites..population.len() {
+        let parent_index = config.selection.select(&fitnesses, rng);
+        let mate_index = if rng.gen_range(0.0, 1.0) >= config.crossover_rate {
+            parent_index
+        } else if within_species {
+            // The mate is selected from the parent's species alone, which keeps the
+            // fittest-first order of the population
+            let species = population[parent_index].species();
+            let candidates: Vec<usize> = (0..population.len())
+                .filter(|&index| population[index].species() == species)
+                .collect();
+            let candidate_fitnesses: Vec<f32> =
+                candidates.iter().map(|&index| fitnesses[index]).collect();
+            candidates[config.selection.select(&candidate_fitnesses, rng)]
+        } else {
+            config.selection.select(&fitnesses, rng)
+        };
+
+        let child = if mate_index == parent_index {
+            population[parent_index].breed(None, rng, fish_config, world, innovations)
+        } else {
+            // The fitter parent comes first, as it passes on the parts of its brain that the
+            // other parent doesn't have
+            let (fitter, other) = (parent_index.min(mate_index), parent_index.max(mate_index));
+            let (fitter_half, other_half) = population.split_at_mut(other);
+            fitter_half[fitter].breed(Some(&other_half[0]), rng, fish_config, world, innovations)
+        };
+        offspring.push(child);
+    }
+
+    // The elites live on into the next generation as if they were newly spawned
+    population.truncate(elites);
+    for elite in population.iter_mut() {
+        elite.respawn(world, rng);
+    }
+    population.append(&mut offspring);
+    population
+}
+
+/// Testing that every selection method favors the fittest fish over the least fit
+#[test]
+fn test_selection() {
+    use rand::SeedableRng;
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let fitnesses = [10.0, 5.0, 1.0, 0.0];
+    for &selection in [
+        Selection::Tournament { size: 3 },
+        Selection::Roulette,
+        Selection::Elitist { fraction: 0.5 },
+    ]
+    .iter()
+    {
+        let mut counts = [0; 4];
+        for _ in 0..1000 {
+            counts[selection.select(&fitnesses, &mut rng)] += 1;
+        }
+        assert!(counts[0] > counts[3], "{:?}: {:?}", selection, counts);
+    }
+}
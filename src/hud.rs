@@ -0,0 +1,78 @@
+//! A module for drawing panels of information on top of the simulation in a `ggez` window.
+
+use ggez::{
+    graphics,
+    graphics::{DrawMode, DrawParam, Mesh, Rect, Text},
+    nalgebra::Point2,
+    Context, GameResult,
+};
+
+/// The space between the edge of the window and a panel, and between a panel and its text
+pub const PANEL_MARGIN: f32 = 5.0;
+
+/// The RGBA color of the background of a panel
+pub const PANEL_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.5];
+
+/// The corners of the window that a panel can be placed in
+#[derive(Debug, Clone, Copy)]
+pub enum Corner {
+    /// The top-left corner of the window
+    TopLeft,
+    /// The top-right corner of the window
+    TopRight,
+    /// The bottom-left corner of the window
+    BottomLeft,
+    /// The bottom-right corner of the window
+    BottomRight,
+}
+
+impl Corner {
+    /// Returns the top-left position of a box of the provided size placed in this corner of a
+    /// window of the provided size
+    pub fn position(self, size: (f32, f32), window_size: (f32, f32)) -> Point2<f32> {
+        let left = PANEL_MARGIN;
+        let top = PANEL_MARGIN;
+        let right = window_size.0 - PANEL_MARGIN - size.0;
+        let bottom = window_size.1 - PANEL_MARGIN - size.1;
+        match self {
+            Corner::TopLeft => Point2::new(left, top),
+            Corner::TopRight => Point2::new(right, top),
+            Corner::BottomLeft => Point2::new(left, bottom),
+            Corner::BottomRight => Point2::new(right, bottom),
+        }
+    }
+}
+
+/// Draws the provided lines of text on a translucent panel in a corner of the window.
+/// This is expected to be drawn with the screen coordinates set to the window's pixels.
+pub fn draw_panel(ctx: &mut Context, lines: &[String], corner: Corner) -> GameResult {
+    if lines.is_empty() {
+        return Ok(());
+    }
+
+    let text = Text::new(lines.join("\n"));
+    let (text_width, text_height) = text.dimensions(ctx);
+    let size = (
+        text_width as f32 + 2.0 * PANEL_MARGIN,
+        text_height as f32 + 2.0 * PANEL_MARGIN,
+    );
+    let pos = corner.position(size, graphics::drawable_size(ctx));
+
+    let background = Mesh::new_rectangle(
+        ctx,
+        DrawMode::fill(),
+        Rect::new(pos.x, pos.y, size.0, size.1),
+        PANEL_COLOR.into(),
+    )?;
+    graphics::draw(ctx, &background, DrawParam::default())?;
+    graphics::draw(
+        ctx,
+        &text,
+        (
+            Point2::new(pos.x + PANEL_MARGIN, pos.y + PANEL_MARGIN),
+            graphics::WHITE,
+        ),
+    )?;
+
+    Ok(())
+}
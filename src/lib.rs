@@ -2,8 +2,25 @@
 
 use ggez::nalgebra::Point2;
 
+pub mod arena;
+pub mod camera;
+pub mod chart;
+pub mod color;
+pub mod config;
+pub mod controller;
+pub mod dna;
+pub mod error;
 pub mod fish;
 pub mod food;
+pub mod generation;
+pub mod hud;
+pub mod neat;
+pub mod simulation;
+pub mod species;
+pub mod stats;
+pub mod sweep;
+pub mod tree;
+pub mod world;
 
 /// Used by the Generic Function `Fish.consume()` to represent a piece of food or a fish
 pub trait Entity {
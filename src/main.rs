@@ -3,157 +3,314 @@
 //! A `Fish` will first spawn at a random location in the window.
 //! The `Fish` will then go on to seek and eat `Food` and `Poison`.
 
-use ggez::{conf, event, graphics, nalgebra::Point2, timer, Context, ContextBuilder, GameResult};
-use rand::{rngs::ThreadRng, Rng};
-use ron::de::from_reader;
-use serde::Deserialize;
-use std::{fs::File, path::PathBuf};
+use ggez::{
+    conf, event,
+    event::{KeyCode, KeyMods},
+    graphics,
+    input::mouse::{self, MouseButton},
+    nalgebra::{Point2, Vector2},
+    timer, Context, ContextBuilder, GameResult,
+};
+use rand::Rng;
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+use structopt::StructOpt;
 
 use evolution::{
-    fish::{Fish, FishConfig},
-    food::{Food, FoodConfig},
+    arena::Mask,
+    camera::Camera,
+    chart::{draw_charts, CHART_HEIGHT},
+    color::{draw_legend, ColorMode},
+    config::{read_file, Config, ConfigChanges},
+    dna::GENE_COUNT,
+    error::Error,
+    fish::Fish,
+    hud::{draw_panel, Corner, PANEL_MARGIN},
+    simulation::Simulation,
+    stats::{AlleleFrequencies, Statistics},
+    sweep::{format_csv, format_table, run_sweep, summary_table, SweepSpec},
+    world::leaves_open_area,
     Entity,
 };
 
-/// The configuration structure that is read and deserialized from `config.ron`
-#[derive(Debug, Deserialize)]
-struct Config {
-    /// Whether or not the window is fullscreen
-    fullscreen: bool,
-    /// If the window is not fullscreen, the size of the window will be specified based on the
-    /// provided (width, height)
-    window_size: (f32, f32),
-    /// Whether or not the current FPS should be displayed in the simulation window
-    show_fps: bool,
-    /// The thickness of the padding boundary for the fish around the window in pixels
-    boundary_padding: f32,
-    /// The configuration pertaining to the fish
-    fish: FishConfig,
-    /// The configuration pertaining to the food
-    food: FoodConfig,
-}
-
-/// The application state that keeps track of all configurations and entities of the simulation
+/// The largest number of ticks of the simulation that can be run per timestep
+const MAX_SPEED_MULTIPLIER: u32 = 1024;
+
+/// The largest number of timesteps that are caught up on per drawn frame.
+/// If the simulation falls further behind than this, it slows down rather than spending ever
+/// longer catching up.
+const MAX_TIMESTEPS_PER_FRAME: u32 = 5;
+
+/// The smallest width and height of the part of the window within the boundary padding that the
+/// window can be resized to
+const MIN_OPEN_WINDOW_SIZE: f32 = 100.0;
+
+/// How often the configuration file is checked for changes
+const CONFIG_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long the outcome of reloading the configuration is displayed for
+const RELOAD_MESSAGE_DURATION: Duration = Duration::from_secs(5);
+
+/// The command-line options of the simulation
+#[derive(Debug, StructOpt)]
+#[structopt(name = "evolution", about = "2D genetic algorithm simulation")]
+struct Options {
+    /// The path of the configuration file.
+    /// Defaults to `config.ron` in the current directory, or else in the crate's directory, or
+    /// else the default configuration is used.
+    #[structopt(short, long, parse(from_os_str))]
+    config: Option<PathBuf>,
+    /// Prints the configuration with every default and override applied as RON, and exits
+    #[structopt(long)]
+    dump_config: bool,
+    /// The path of the assets folder.
+    /// Defaults to `assets` in the current directory, or else in the crate's directory.
+    #[structopt(short, long, parse(from_os_str))]
+    assets: Option<PathBuf>,
+    /// The seed of the random number generator, so that runs can be reproduced.
+    /// A random seed is chosen and printed if none is provided.
+    #[structopt(short, long)]
+    seed: Option<u64>,
+    /// Runs the simulation as fast as possible without opening a window
+    #[structopt(long, requires = "ticks")]
+    headless: bool,
+    /// The number of ticks to run before exiting
+    #[structopt(short, long)]
+    ticks: Option<u64>,
+    /// The directory that the configuration of the run and the statistics of every tick are
+    /// written to
+    #[structopt(short, long, parse(from_os_str))]
+    output: Option<PathBuf>,
+    /// Overrides a field of the configuration, e.g. `--set fish.mutation_rate=0.05`.
+    /// Can be provided several times.
+    #[structopt(long = "set", number_of_values = 1)]
+    overrides: Vec<String>,
+    /// The path of a sweep specification. Every combination of its parameters is run headless
+    /// once per seed, and a summary of each run is printed and written to the output directory.
+    #[structopt(long, parse(from_os_str), conflicts_with = "headless")]
+    sweep: Option<PathBuf>,
+}
+
+/// Watches the configuration file so that changes to it are applied while the simulation runs
+struct ConfigWatcher {
+    /// The path of the configuration file, if there is one
+    path: Option<PathBuf>,
+    /// The overrides from the command line, which are reapplied whenever the file is reloaded
+    overrides: Vec<String>,
+    /// When the file was last modified, as of the last time it was checked
+    modified: Option<SystemTime>,
+    /// When the file was last checked for changes
+    last_check: Instant,
+}
+
+impl ConfigWatcher {
+    /// Creates a new watcher of the configuration file at the provided path
+    fn new(path: Option<PathBuf>, overrides: Vec<String>) -> Self {
+        Self {
+            modified: path.as_deref().and_then(modified_time),
+            path,
+            overrides,
+            last_check: Instant::now(),
+        }
+    }
+
+    /// Reads the configuration file, or uses the default configuration if there is none, and
+    /// then applies the overrides and checks every field
+    fn load(&self) -> Result<Config, Error> {
+        let mut config = match &self.path {
+            Some(path) => Config::load(path)?,
+            None => Config::default(),
+        };
+        // Overrides from the environment are applied first, so that those on the command line
+        // take precedence over them
+        config.apply_env_overrides(std::env::vars_os().filter_map(|(name, value)| {
+            Some((name.into_string().ok()?, value.into_string().ok()?))
+        }))?;
+        for assignment in self.overrides.iter() {
+            config.apply_override(assignment)?;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Returns the reloaded configuration if the file has changed since it was last checked
+    fn poll(&mut self) -> Option<Result<Config, Error>> {
+        if self.last_check.elapsed() < CONFIG_CHECK_INTERVAL {
+            return None;
+        }
+        self.last_check = Instant::now();
+
+        let modified = self.path.as_deref().and_then(modified_time);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        Some(self.load())
+    }
+}
+
+/// The files that a run of the simulation is recorded to
+struct Recorder {
+    /// The CSV table of the statistics of every tick
+    stats: BufWriter<File>,
+}
+
+impl Recorder {
+    /// Creates the output directory if needed, and writes the configuration and seed of the run
+    /// to it so that the run can be reproduced
+    fn create(dir: &Path, config: &Config, seed: u64) -> Result<Self, Error> {
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| Error::Io { path, source }
+        };
+
+        fs::create_dir_all(dir).map_err(io_error(dir))?;
+        let config_path = dir.join("config.ron");
+        fs::write(
+            &config_path,
+            format!("// Run with `--seed {}`\n{}\n", seed, config.to_ron()?),
+        )
+        .map_err(io_error(&config_path))?;
+        let stats_path = dir.join("stats.csv");
+        let mut stats = BufWriter::new(File::create(&stats_path).map_err(io_error(&stats_path))?);
+        writeln!(
+            stats,
+            "{}",
+            Statistics::csv_header(config.fish.total_food_chain_links)
+        )
+        .map_err(io_error(&stats_path))?;
+        Ok(Self { stats })
+    }
+
+    /// Writes the statistics of the tick that was just run
+    fn record(&mut self, simulation: &Simulation) -> GameResult {
+        let statistics = Statistics::new(&simulation.fish_groups);
+        writeln!(self.stats, "{}", statistics.csv_row(simulation.tick))?;
+        Ok(())
+    }
+}
+
+/// The application state that keeps track of the simulation and how it is displayed
 struct State {
-    /// The configuration information set from `config.ron` when the program was executed
-    config: Config,
-    /// Random number generator
-    rng: ThreadRng,
-    /// A collection of food
-    food: Vec<Food>,
-    /// A collection of fish groups who are organized based on their level in the food chain
-    fish_groups: Vec<Vec<Fish>>,
-    /// The spritesheet of the fish used for its animation
-    fish_image: graphics::Image,
+    /// The configurations and entities of the simulation
+    simulation: Simulation,
+    /// The watcher of the configuration file, for reloading it while the simulation runs
+    config_watcher: ConfigWatcher,
+    /// The outcome of the last time the configuration was reloaded, and when that was
+    reload_message: Option<(Vec<String>, Instant)>,
+    /// Where the run is recorded to, if anywhere
+    recorder: Option<Recorder>,
+    /// The number of ticks to run before exiting, if limited
+    max_ticks: Option<u64>,
+    /// The batch of every fish's animation frame from the spritesheet, which is drawn at once
+    fish_batch: graphics::spritebatch::SpriteBatch,
+    /// The view into the world that is displayed in the window
+    camera: Camera,
+    /// The id of the fish that was clicked on to be inspected, if any
+    selected: Option<u64>,
+    /// Whether or not the mouse was dragged while the left mouse button was held down
+    dragged: bool,
+    /// The number of ticks of the simulation that are run per timestep
+    speed_multiplier: u32,
+    /// Whether or not the simulation is paused
+    paused: bool,
+    /// Whether or not a single tick should be run while the simulation is paused
+    step_requested: bool,
+    /// Whether or not the debugging information of every fish is drawn
+    show_debug: bool,
+    /// Whether or not the charts of the population and genes are drawn
+    show_charts: bool,
+    /// The index of the gene in the DNA that is plotted in the charts
+    chart_gene_index: usize,
+    /// What the color of each fish represents
+    color_mode: ColorMode,
 }
 
 impl State {
     /// Creates a new instance of the application state
-    fn new(ctx: &mut Context, config: Config) -> GameResult<State> {
-        // Random number generator is used for psuedo-random components of this
-        // simulation
-        let mut rng = rand::thread_rng();
-
-        let mut food = Vec::new();
-        // Spawn the food
-        for _ in 1..config.food.quantity {
-            Self::add_food(&mut food, &config, &mut rng);
-        }
-
-        let mut fish_groups = Vec::new();
-
-        let fish_per_group = config.fish.quantity / config.fish.total_food_chain_links;
-        // Spawn the fish
-        for group_index in 0..config.fish.total_food_chain_links {
-            fish_groups.push(Vec::new());
-            for _ in 0..fish_per_group {
-                fish_groups[group_index].push(Fish::new(
-                    &config.fish,
-                    &group_index,
-                    &config.window_size,
-                    &mut rng,
-                ));
-            }
-        }
-
+    fn new(
+        ctx: &mut Context,
+        simulation: Simulation,
+        config_watcher: ConfigWatcher,
+        recorder: Option<Recorder>,
+        max_ticks: Option<u64>,
+    ) -> GameResult<State> {
         // Retrieve the spritesheet for the fish animation
-        let mut fish_image = graphics::Image::new(ctx, "/frames.png").unwrap();
+        let mut fish_image = graphics::Image::new(ctx, "/frames.png")?;
         // This makes the pixel art visibly sharp, rather than blurry
         fish_image.set_filter(graphics::FilterMode::Nearest);
 
         Ok(State {
-            config,
-            rng,
-            fish_groups,
-            food,
-            fish_image,
+            camera: Camera::new(simulation.world.size),
+            simulation,
+            config_watcher,
+            reload_message: None,
+            recorder,
+            max_ticks,
+            selected: None,
+            dragged: false,
+            speed_multiplier: 1,
+            paused: false,
+            step_requested: false,
+            show_debug: false,
+            show_charts: false,
+            chart_gene_index: 0,
+            color_mode: ColorMode::Lineage,
+            fish_batch: graphics::spritebatch::SpriteBatch::new(fish_image),
         })
     }
-
-    /// Adds a peice of food to the collection
-    fn add_food(food: &mut Vec<Food>, config: &Config, rng: &mut ThreadRng) {
-        food.push(Food::new(Point2::new(
-            rng.gen_range(
-                config.boundary_padding,
-                config.window_size.0 - config.boundary_padding,
-            ),
-            rng.gen_range(
-                config.boundary_padding,
-                config.window_size.1 - config.boundary_padding,
-            ),
-        )));
-    }
 }
 
 impl event::EventHandler for State {
-    /// Updates all elements of the current application state
-    fn update(&mut self, _ctx: &mut Context) -> GameResult {
-        if self.rng.gen_ratio(1, 10) {
-            Self::add_food(&mut self.food, &self.config, &mut self.rng);
-        }
-
-        for group_index in 0..self.config.fish.total_food_chain_links {
-            let (prey, other_fish_groups) = self.fish_groups.split_at_mut(group_index);
-
-            let predator_positions = if group_index == self.config.fish.total_food_chain_links - 1 {
-                None
-            } else {
-                Some(
-                    other_fish_groups[1]
-                        .iter()
-                        .map(|predator| predator.pos())
-                        .collect(),
-                )
+    /// Updates all elements of the current application state, running as many ticks of the
+    /// simulation as the speed multiplier allows for every timestep that has passed, unless it
+    /// is paused
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        // Changes to the configuration file are applied without restarting, where possible
+        if let Some(reloaded) = self.config_watcher.poll() {
+            let lines = match reloaded.and_then(|config| self.simulation.reload_config(&config)) {
+                Ok(changes) => reload_lines(&changes),
+                Err(e) => format!("Config not reloaded: {}", e)
+                    .lines()
+                    .map(str::to_string)
+                    .collect(),
             };
-
-            // We should remove dead fish from our collection of fish
-            other_fish_groups[0].retain(|fish| fish.is_alive());
-
-            let mut new_fish = None;
-
-            for fish in other_fish_groups[0].iter_mut() {
-                // Only update living fish
-                if fish.is_alive() {
-                    if new_fish.is_none() && self.rng.gen_ratio(1, 1000) {
-                        new_fish = Some(fish.clone(&mut self.rng, self.config.fish.mutation_rate));
-                    }
-                    // Update the behavior state of all fish
-                    fish.behave(
-                        &mut self.food,
-                        prey,
-                        &predator_positions,
-                        self.config.fish.eating_radius,
-                    );
-                    // Bound the fish to a padding in the window
-                    fish.bound(&self.config.window_size, self.config.boundary_padding);
-                    // Update the physical state of all fish
-                    fish.update();
-                }
+            for line in lines.iter() {
+                println!("{}", line);
             }
+            self.reload_message = Some((lines, Instant::now()));
+        }
 
-            if let Some(new_fish) = new_fish {
-                other_fish_groups[0].push(new_fish)
-            };
+        let mut timesteps = 0;
+        // Every timestep that has passed is consumed, even if it isn't run, so that the
+        // simulation doesn't rush to catch up after being paused or falling behind
+        while timer::check_update_time(ctx, self.simulation.config.ticks_per_second) {
+            timesteps += 1;
+        }
+        let timesteps = timesteps.min(MAX_TIMESTEPS_PER_FRAME);
+
+        let ticks = if !self.paused {
+            timesteps * self.speed_multiplier
+        } else if self.step_requested {
+            1
+        } else {
+            0
+        };
+        self.step_requested = false;
+
+        for _ in 0..ticks {
+            self.simulation.tick();
+            if let Some(recorder) = &mut self.recorder {
+                recorder.record(&self.simulation)?;
+            }
+            if Some(self.simulation.tick) == self.max_ticks {
+                event::quit(ctx);
+                break;
+            }
         }
 
         Ok(())
@@ -164,57 +321,484 @@ impl event::EventHandler for State {
         // Sets the background to a solid blue-ish color
         graphics::clear(ctx, [0.1, 0.2, 0.3, 1.0].into());
 
-        for food in self.food.iter() {
-            if let Err(error) = food.draw(ctx) {
-                return Err(error);
+        // How far between the last tick and the next the simulation is, so that fish move
+        // smoothly even when the window is drawn more often than the simulation is ticked
+        let alpha = if self.paused {
+            1.0
+        } else {
+            let timestep = 1.0 / self.simulation.config.ticks_per_second as f64;
+            (timer::duration_to_f64(timer::remaining_update_time(ctx)) / timestep).min(1.0) as f32
+        };
+
+        // Keep the followed fish centered, or stop following it once it has died
+        if let Some(id) = self.camera.following {
+            match self
+                .simulation
+                .find_fish(id)
+                .map(|fish| fish.interpolated_pos(alpha, &self.simulation.world))
+            {
+                Some(pos) => self.camera.center = pos,
+                None => self.camera.following = None,
             }
         }
 
-        for fish_group in self.fish_groups.iter_mut() {
-            for fish in fish_group.iter_mut() {
-                if let Err(error) = fish.draw(
-                    ctx,
-                    &self.fish_image,
-                    self.config.fish.frames_per_animation_frame,
-                ) {
-                    return Err(error);
+        // The world is drawn in world coordinates, through the view of the camera
+        let window_size = graphics::drawable_size(ctx);
+        let world_view = self.camera.view(window_size, self.simulation.world.size);
+        graphics::set_screen_coordinates(ctx, world_view)?;
+
+        self.simulation
+            .world
+            .arena
+            .draw(ctx, self.simulation.world.size)?;
+
+        // Every piece of food is drawn at once as a single mesh
+        if !self.simulation.food.is_empty() {
+            let mut food_builder = graphics::MeshBuilder::new();
+            for food in self.simulation.food.iter() {
+                food.draw(&mut food_builder);
+            }
+            let food_mesh = food_builder.build(ctx)?;
+            graphics::draw(ctx, &food_mesh, graphics::DrawParam::default())?;
+        }
+
+        let color_range = self.color_mode.range(&self.simulation.fish_groups);
+        for fish_group in self.simulation.fish_groups.iter() {
+            for fish in fish_group.iter() {
+                let color = self.color_mode.color(fish, color_range);
+                fish.draw(&mut self.fish_batch, color, alpha, &self.simulation.world);
+            }
+        }
+        // Every fish is drawn at once from a single sprite batch
+        graphics::draw(ctx, &self.fish_batch, graphics::DrawParam::default())?;
+        self.fish_batch.clear();
+
+        // The debugging information of every fish is batched into a single mesh
+        if self.show_debug {
+            let mut builder = graphics::MeshBuilder::new();
+            for fish in self.simulation.fish_groups.iter().flatten() {
+                fish.draw_debug(&mut builder, &self.simulation.world)?;
+            }
+            if self
+                .simulation
+                .fish_groups
+                .iter()
+                .any(|fish_group| !fish_group.is_empty())
+            {
+                let debug_mesh = builder.build(ctx)?;
+                graphics::draw(ctx, &debug_mesh, graphics::DrawParam::default())?;
+            }
+        }
+
+        // Highlight the selected fish, or forget it once it has died
+        if let Some(id) = self.selected {
+            match self.simulation.find_fish(id).map(|fish| {
+                (
+                    fish.interpolated_pos(alpha, &self.simulation.world),
+                    fish.radius(),
+                )
+            }) {
+                Some((pos, radius)) => {
+                    let highlight = graphics::Mesh::new_circle(
+                        ctx,
+                        graphics::DrawMode::stroke(2.0),
+                        pos,
+                        radius,
+                        0.5,
+                        graphics::WHITE,
+                    )?;
+                    graphics::draw(ctx, &highlight, graphics::DrawParam::default())?;
                 }
+                None => self.selected = None,
             }
         }
 
-        if self.config.show_fps {
+        // The heads-up display is drawn in window coordinates, on top of the world
+        graphics::set_screen_coordinates(
+            ctx,
+            graphics::Rect::new(0.0, 0.0, window_size.0, window_size.1),
+        )?;
+
+        // The heads-up display text is stacked downwards from the top-left corner
+        let mut hud_lines = vec![format!(
+            "Tick: {} | Speed: x{}{}",
+            self.simulation.tick,
+            self.speed_multiplier,
+            if self.paused { " (paused)" } else { "" }
+        )];
+        if let Some(generational) = &self.simulation.config.generational {
+            hud_lines.push(format!(
+                "Generation: {} ({} / {} ticks)",
+                self.simulation.generation,
+                self.simulation.tick % generational.generation_ticks,
+                generational.generation_ticks
+            ));
+        }
+        if self.simulation.config.show_fps {
             let fps = timer::fps(ctx);
-            let fps_text = graphics::Text::new(format!("FPS: {:.*}", 1, fps));
-            graphics::draw(ctx, &fps_text, (Point2::new(5.0, 5.0), graphics::WHITE))?;
+            hud_lines.push(format!("FPS: {:.*}", 1, fps));
+        }
+        if self.simulation.config.show_stats {
+            hud_lines.extend(Statistics::new(&self.simulation.fish_groups).lines());
+            if let Some(frequencies) = AlleleFrequencies::new(
+                &self.simulation.fish_groups,
+                &self.simulation.config.fish.genes,
+            ) {
+                hud_lines.extend(frequencies.lines());
+            }
+            if self.simulation.config.speciation.is_some() {
+                hud_lines.extend(self.simulation.species.lines());
+            }
+        }
+        if let Some((lines, reloaded_at)) = &self.reload_message {
+            if reloaded_at.elapsed() < RELOAD_MESSAGE_DURATION {
+                hud_lines.extend(lines.iter().cloned());
+            }
+        }
+        draw_panel(ctx, &hud_lines, Corner::TopLeft)?;
+
+        if self.show_charts {
+            draw_charts(
+                ctx,
+                &self.simulation.history,
+                &self.simulation.fish_groups,
+                self.chart_gene_index,
+            )?;
+        }
+
+        // The legend of the fish colors is drawn above the charts, if they are drawn
+        let raised_by = if self.show_charts {
+            CHART_HEIGHT + PANEL_MARGIN
+        } else {
+            0.0
+        };
+        draw_legend(ctx, self.color_mode, color_range, raised_by)?;
+
+        // The selected fish is described in the top-right corner
+        if let Some(fish) = self.selected.and_then(|id| self.simulation.find_fish(id)) {
+            draw_panel(ctx, &fish.inspect_lines(), Corner::TopRight)?;
         }
 
         graphics::present(ctx)?;
 
         Ok(())
     }
+
+    /// Resizes the world along with the window, unless the world has a fixed size
+    fn resize_event(&mut self, _ctx: &mut Context, width: f32, height: f32) {
+        // A window that is minimised or smaller than the boundary padding keeps the last world
+        // that had room to spawn fish and food in
+        let padding = self.simulation.config.boundary_padding;
+        if self.simulation.config.world_size.is_none() && leaves_open_area((width, height), padding)
+        {
+            self.simulation.resize_world((width, height));
+            self.camera.keep_within(self.simulation.world.size);
+        }
+    }
+
+    /// Zooms the camera in or out around the mouse cursor
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, _x: f32, y: f32) {
+        let cursor = mouse::position(ctx);
+        self.camera.zoom_at(
+            Point2::new(cursor.x, cursor.y),
+            1.1f32.powf(y),
+            graphics::drawable_size(ctx),
+            self.simulation.world.size,
+        );
+    }
+
+    /// Starts keeping track of whether the left mouse button is being dragged or clicked
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) {
+        if button == MouseButton::Left {
+            self.dragged = false;
+        }
+    }
+
+    /// Selects the fish that was clicked on to be inspected, or deselects the inspected fish if
+    /// no fish was clicked on
+    fn mouse_button_up_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        if button == MouseButton::Left && !self.dragged {
+            let pos = self.camera.screen_to_world(
+                Point2::new(x, y),
+                graphics::drawable_size(ctx),
+                self.simulation.world.size,
+            );
+            self.selected = self.simulation.fish_at(pos).map(Fish::id);
+        }
+    }
+
+    /// Pans the camera while the left mouse button is dragged
+    fn mouse_motion_event(&mut self, ctx: &mut Context, _x: f32, _y: f32, dx: f32, dy: f32) {
+        if mouse::button_pressed(ctx, MouseButton::Left) {
+            self.dragged = true;
+            self.camera.pan(
+                Vector2::new(dx, dy),
+                graphics::drawable_size(ctx),
+                self.simulation.world.size,
+            );
+        }
+    }
+
+    /// Handles the keyboard controls of the simulation
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        keycode: KeyCode,
+        _keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        match keycode {
+            KeyCode::Escape => event::quit(ctx),
+            // Toggles following the selected fish, or the fish nearest to the center of the
+            // camera if no fish is selected
+            KeyCode::F => {
+                self.camera.following = match self.camera.following {
+                    Some(_) => None,
+                    None => self.selected.or_else(|| {
+                        self.simulation
+                            .nearest_fish(self.camera.center)
+                            .map(Fish::id)
+                    }),
+                }
+            }
+            // Toggles drawing the debugging information of every fish
+            KeyCode::D => self.show_debug = !self.show_debug,
+            // Toggles drawing the charts of the population and genes
+            KeyCode::C => self.show_charts = !self.show_charts,
+            // Cycles through the gene that is plotted in the charts
+            KeyCode::G => self.chart_gene_index = (self.chart_gene_index + 1) % GENE_COUNT,
+            // Cycles through what the color of each fish represents
+            KeyCode::V => self.color_mode = self.color_mode.next(),
+            // Pauses or resumes the simulation
+            KeyCode::Space => self.paused = !self.paused,
+            // Runs a single tick of the simulation while it is paused
+            KeyCode::Period => self.step_requested = true,
+            // Doubles or halves the number of ticks run per timestep
+            KeyCode::Up => {
+                self.speed_multiplier = (self.speed_multiplier * 2).min(MAX_SPEED_MULTIPLIER)
+            }
+            KeyCode::Down => self.speed_multiplier = (self.speed_multiplier / 2).max(1),
+            // Resets the camera to fit the whole world within the window
+            KeyCode::Home => self.camera = Camera::new(self.simulation.world.size),
+            _ => (),
+        }
+    }
+}
+
+/// Returns the provided path, or else the file or folder of the provided name in the current
+/// directory if it exists, or else in the crate's directory
+fn resolve_path(path: Option<PathBuf>, name: &str) -> PathBuf {
+    path.unwrap_or_else(|| {
+        let local = PathBuf::from(name);
+        if local.exists() {
+            local
+        } else {
+            Path::new(env!("CARGO_MANIFEST_DIR")).join(name)
+        }
+    })
+}
+
+/// Returns when the file at the provided path was last modified, if it can be read
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Returns lines of text describing which fields of a reloaded configuration were applied, and
+/// which need a restart
+fn reload_lines(changes: &ConfigChanges) -> Vec<String> {
+    if changes.applied.is_empty() && changes.needs_restart.is_empty() {
+        return vec!["Config reloaded with no changes".to_string()];
+    }
+    let mut lines = vec!["Config reloaded".to_string()];
+    if !changes.applied.is_empty() {
+        lines.push(format!("Applied: {}", changes.applied.join(", ")));
+    }
+    if !changes.needs_restart.is_empty() {
+        lines.push(format!(
+            "Needs a restart: {}",
+            changes.needs_restart.join(", ")
+        ));
+    }
+    lines
+}
+
+/// Reads the image mask of the arena's walls from the assets folder, if one is provided
+fn read_mask(config: &Config, assets_dir: &Path) -> Result<Option<Mask>, Error> {
+    match &config.arena.mask {
+        // Like `ggez::filesystem`, paths within the assets folder start with a slash
+        Some(mask_path) => {
+            let path = assets_dir.join(mask_path.trim_start_matches('/'));
+            let bytes = fs::read(&path).map_err(|source| Error::Io { path, source })?;
+            Ok(Some(Mask::from_png(&bytes)?))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Runs the provided number of ticks of the simulation as fast as possible without a window,
+/// and prints the statistics of the population at the end
+fn run_headless(
+    mut simulation: Simulation,
+    mut recorder: Option<Recorder>,
+    ticks: u64,
+) -> GameResult {
+    while simulation.tick < ticks {
+        simulation.tick();
+        if let Some(recorder) = &mut recorder {
+            recorder.record(&simulation)?;
+        }
+    }
+    if let Some(recorder) = &mut recorder {
+        recorder.stats.flush()?;
+    }
+
+    println!("Tick: {}", simulation.tick);
+    if simulation.config.generational.is_some() {
+        println!("Generation: {}", simulation.generation);
+    }
+    for line in Statistics::new(&simulation.fish_groups).lines() {
+        println!("{}", line);
+    }
+    if let Some(frequencies) =
+        AlleleFrequencies::new(&simulation.fish_groups, &simulation.config.fish.genes)
+    {
+        for line in frequencies.lines() {
+            println!("{}", line);
+        }
+    }
+    if simulation.config.speciation.is_some() {
+        for line in simulation.species.lines() {
+            println!("{}", line);
+        }
+    }
+    Ok(())
+}
+
+/// Runs every run of the sweep at the provided path, printing each one as it finishes and a
+/// table of all of them at the end, which is also written as CSV to the output directory
+fn run_sweep_batch(
+    sweep_path: &Path,
+    config: &Config,
+    assets_dir: &Path,
+    output_dir: Option<&Path>,
+) -> Result<(), Error> {
+    let spec: SweepSpec = read_file(sweep_path)?;
+    let run_count = spec.run_count();
+    let mut finished = 0;
+    let summaries = run_sweep(
+        &spec,
+        config,
+        |config| read_mask(config, assets_dir),
+        |summary| {
+            finished += 1;
+            println!(
+                "Run {}/{}: [{}] seed {}, population {} after {} ticks",
+                finished,
+                run_count,
+                summary.assignments.join(", "),
+                summary.seed,
+                summary.statistics.population(),
+                summary.ticks
+            );
+        },
+    )?;
+
+    let table = summary_table(&spec, &summaries);
+    println!("{}", format_table(&table));
+
+    if let Some(output_dir) = output_dir {
+        fs::create_dir_all(output_dir).map_err(|source| Error::Io {
+            path: output_dir.to_path_buf(),
+            source,
+        })?;
+        let path = output_dir.join("summary.csv");
+        fs::write(&path, format_csv(&table)).map_err(|source| Error::Io { path, source })?;
+    }
+    Ok(())
 }
 
 /// The main function :D
-pub fn main() -> GameResult {
+pub fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Reads and checks the configuration and assets, and then runs the simulation either in a
+/// window or headless
+fn run() -> Result<(), Error> {
+    let options = Options::from_args();
+
     // Specifying the path for the configuration file and deserializing its data
-    let input_path = format!("{}/config.ron", env!("CARGO_MANIFEST_DIR"));
-    let f = File::open(&input_path)?;
-    let config: Config = match from_reader(f) {
-        Ok(x) => x,
-        Err(e) => {
-            println!("Failed to load `config.ron`: {}", e);
-            std::process::exit(1);
-        }
-    };
+    let config_path = options
+        .config
+        .or_else(|| Some(resolve_path(None, "config.ron")).filter(|path| path.exists()));
+    let config_watcher = ConfigWatcher::new(config_path, options.overrides);
+    // Every problem with the configuration is reported before the window opens
+    let config = config_watcher.load()?;
+
+    if options.dump_config {
+        println!("{}", config.to_ron()?);
+        return Ok(());
+    }
 
     // Setting the path for the assets folder
-    let assets_dir = PathBuf::from(format!("{}/assets", env!("CARGO_MANIFEST_DIR")));
+    let assets_dir = resolve_path(options.assets, "assets");
+    if let Some(sweep_path) = &options.sweep {
+        return run_sweep_batch(sweep_path, &config, &assets_dir, options.output.as_deref());
+    }
+
+    let mask = read_mask(&config, &assets_dir)?;
+
+    let seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    println!("Seed: {}", seed);
+
+    let recorder = match &options.output {
+        Some(output_dir) => Some(Recorder::create(output_dir, &config, seed)?),
+        None => None,
+    };
+
+    if options.headless {
+        let world_size = config.headless_world_size();
+        let simulation = Simulation::new(config, world_size, mask, seed);
+        return Ok(run_headless(
+            simulation,
+            recorder,
+            options.ticks.unwrap_or(0),
+        )?);
+    }
+
+    // The spritesheet is only needed to draw the fish in a window
+    let frames_path = assets_dir.join("frames.png");
+    if !frames_path.is_file() {
+        return Err(Error::MissingFile {
+            path: frames_path,
+            suggestion: "the fish spritesheet is read from the assets folder, which can be set \
+                         with `--assets <path>`"
+                .to_string(),
+        });
+    }
 
     // If not fullscreen, display window based on provided dimensions
     let window_settings = if config.fullscreen {
         conf::WindowMode::default().fullscreen_type(conf::FullscreenType::True)
     } else {
-        conf::WindowMode::default().dimensions(config.window_size.0, config.window_size.1)
+        conf::WindowMode::default()
+            .dimensions(config.window_size.0, config.window_size.1)
+            .min_dimensions(
+                2.0 * config.boundary_padding + MIN_OPEN_WINDOW_SIZE,
+                2.0 * config.boundary_padding + MIN_OPEN_WINDOW_SIZE,
+            )
+            .resizable(true)
     };
 
     // Setting-up the simulation and running it
@@ -224,7 +808,13 @@ pub fn main() -> GameResult {
         .add_resource_path(assets_dir)
         .build()?;
 
-    let state = &mut State::new(ctx, config)?;
+    // The world is either a fixed size or the size of the window that was actually created,
+    // which can differ from `Config.window_size` in fullscreen mode
+    let world_size = config
+        .world_size
+        .unwrap_or_else(|| graphics::drawable_size(ctx));
+    let simulation = Simulation::new(config, world_size, mask, seed);
+    let state = &mut State::new(ctx, simulation, config_watcher, recorder, options.ticks)?;
 
-    event::run(ctx, event_loop, state)
+    Ok(event::run(ctx, event_loop, state)?)
 }
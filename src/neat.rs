@@ -0,0 +1,446 @@
+//! A module for neural networks whose topology evolves, following NEAT (NeuroEvolution of
+//! Augmenting Topologies): connections are tagged with innovation numbers so that networks of
+//! different shapes can be crossed over and compared.
+
+use rand::{rngs::StdRng, Rng};
+use rand_distr::{Distribution, Normal};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The number of attempts at finding two nodes that can be connected by an add-connection
+/// mutation before giving up
+const ADD_CONNECTION_ATTEMPTS: usize = 20;
+
+/// The configuration of the NEAT controller that is read and deserialized from `config.ron`.
+/// Any field that is left out takes its value from `NeatConfig::default()`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NeatConfig {
+    /// The chance of each connection's weight being perturbed when an offspring is born
+    pub weight_mutation_rate: f32,
+    /// The standard deviation of the normally distributed perturbation of a weight
+    pub weight_sigma: f32,
+    /// The chance of an offspring's network gaining a connection between two unconnected nodes
+    pub add_connection_rate: f32,
+    /// The chance of an offspring's network gaining a node that splits one of its connections
+    pub add_node_rate: f32,
+    /// The compatibility distance added for each connection beyond the other network's newest
+    /// innovation
+    pub excess_coefficient: f32,
+    /// The compatibility distance added for each other connection that only one network has
+    pub disjoint_coefficient: f32,
+    /// The compatibility distance added per unit of mean weight difference between the
+    /// connections that both networks have
+    pub weight_coefficient: f32,
+    /// Whether or not a fish's fitness in generational mode is divided by the size of its
+    /// species, so that new structures aren't crowded out by a single large species.
+    /// Has no effect unless speciation is enabled.
+    pub fitness_sharing: bool,
+}
+
+impl Default for NeatConfig {
+    fn default() -> Self {
+        Self {
+            weight_mutation_rate: 0.8,
+            weight_sigma: 0.5,
+            add_connection_rate: 0.05,
+            add_node_rate: 0.03,
+            excess_coefficient: 0.05,
+            disjoint_coefficient: 0.05,
+            weight_coefficient: 0.1,
+            fitness_sharing: true,
+        }
+    }
+}
+
+/// The innovation numbers and hidden node ids that have been handed out, which are shared by
+/// every network of a simulation so that the same structural mutation always gets the same
+/// numbers. Each simulation has its own, so that runs with the same seed number their networks
+/// alike.
+#[derive(Debug, Default)]
+pub struct Innovations {
+    /// The innovation number of the connection between each pair of node ids
+    connections: BTreeMap<(u64, u64), u64>,
+    /// The id of the hidden node that splits the connection with each innovation number
+    splits: BTreeMap<u64, u64>,
+    /// The innovation number that will be given to the next new connection
+    next_innovation: u64,
+    /// The id that will be given to the next new hidden node, once the number of inputs and
+    /// outputs is known
+    next_node: Option<u64>,
+}
+
+impl Innovations {
+    /// Returns the innovation number of the connection between the provided nodes
+    fn connection(&mut self, from: u64, to: u64) -> u64 {
+        let next_innovation = &mut self.next_innovation;
+        *self.connections.entry((from, to)).or_insert_with(|| {
+            *next_innovation += 1;
+            *next_innovation - 1
+        })
+    }
+
+    /// Returns the id of the hidden node that splits the connection with the provided
+    /// innovation number, where the input and output nodes take the ids below `first_hidden`
+    fn split(&mut self, innovation: u64, first_hidden: u64) -> u64 {
+        let next_node = self.next_node.get_or_insert(first_hidden);
+        *self.splits.entry(innovation).or_insert_with(|| {
+            *next_node += 1;
+            *next_node - 1
+        })
+    }
+}
+
+/// A connection between two nodes of a network
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionGene {
+    /// The number that identifies the structural mutation that created the connection
+    pub innovation: u64,
+    /// The id of the node that the connection starts from
+    pub from: u64,
+    /// The id of the node that the connection leads to
+    pub to: u64,
+    /// The weight that the value of `from` is multiplied by
+    pub weight: f32,
+    /// Whether or not the connection is used, as connections that were split by a new node are
+    /// kept to line networks up by innovation
+    pub enabled: bool,
+}
+
+/// The genes of a feed-forward network, where the input nodes have the ids `0..inputs`, the
+/// output nodes have the ids `inputs..inputs + outputs`, and hidden nodes have the ids after them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Genome {
+    /// The number of input nodes
+    inputs: usize,
+    /// The number of output nodes
+    outputs: usize,
+    /// The ids of the hidden nodes
+    hidden: Vec<u64>,
+    /// The connections between the nodes, in the order they were gained
+    connections: Vec<ConnectionGene>,
+}
+
+impl Genome {
+    /// Creates a network that connects every input directly to every output with a random weight
+    pub fn new(
+        inputs: usize,
+        outputs: usize,
+        innovations: &mut Innovations,
+        rng: &mut StdRng,
+    ) -> Self {
+        let mut connections = Vec::with_capacity(inputs * outputs);
+        for to in inputs..inputs + outputs {
+            for from in 0..inputs {
+                connections.push(ConnectionGene {
+                    innovation: innovations.connection(from as u64, to as u64),
+                    from: from as u64,
+                    to: to as u64,
+                    weight: rng.gen_range(-1.0, 1.0),
+                    enabled: true,
+                });
+            }
+        }
+        Self {
+            inputs,
+            outputs,
+            hidden: Vec::new(),
+            connections,
+        }
+    }
+
+    /// Returns the values of the output nodes for the provided values of the input nodes.
+    /// Hidden and output nodes apply `tanh` to the weighted sum of their incoming connections.
+    pub fn activate(&self, inputs: &[f32]) -> Vec<f32> {
+        let mut values: BTreeMap<u64, f32> = inputs
+            .iter()
+            .take(self.inputs)
+            .enumerate()
+            .map(|(id, value)| (id as u64, *value))
+            .collect();
+        for id in self.evaluation_order() {
+            let sum: f32 = self
+                .connections
+                .iter()
+                .filter(|connection| connection.enabled && connection.to == id)
+                .map(|connection| {
+                    connection.weight * values.get(&connection.from).copied().unwrap_or(0.0)
+                })
+                .sum();
+            values.insert(id, sum.tanh());
+        }
+        (self.inputs..self.inputs + self.outputs)
+            .map(|id| values[&(id as u64)])
+            .collect()
+    }
+
+    /// Returns the ids of the hidden and output nodes in an order where every node comes after
+    /// the nodes that its enabled connections start from
+    fn evaluation_order(&self) -> Vec<u64> {
+        let mut remaining: Vec<u64> = self.hidden.clone();
+        remaining.extend((self.inputs..self.inputs + self.outputs).map(|id| id as u64));
+        let mut evaluated: BTreeSet<u64> = (0..self.inputs as u64).collect();
+        let mut order = Vec::with_capacity(remaining.len());
+        while !remaining.is_empty() {
+            let ready = remaining.iter().position(|&id| {
+                self.connections.iter().all(|connection| {
+                    !connection.enabled
+                        || connection.to != id
+                        || evaluated.contains(&connection.from)
+                })
+            });
+            // Add-connection mutations never create cycles, so a node is always ready
+            let id = remaining.remove(ready.unwrap_or(0));
+            evaluated.insert(id);
+            order.push(id);
+        }
+        order
+    }
+
+    /// Returns the offspring of this network and a mate, where connections that both networks
+    /// have take their weight from either at random and the other connections are taken from
+    /// this network, which should be the fitter of the two
+    pub fn crossover(&self, mate: &Genome, rng: &mut StdRng) -> Self {
+        let mate_connections: BTreeMap<u64, &ConnectionGene> = mate
+            .connections
+            .iter()
+            .map(|connection| (connection.innovation, connection))
+            .collect();
+        let connections = self
+            .connections
+            .iter()
+            .map(
+                |connection| match mate_connections.get(&connection.innovation) {
+                    Some(mate_connection) if rng.gen_bool(0.5) => ConnectionGene {
+                        weight: mate_connection.weight,
+                        enabled: mate_connection.enabled || connection.enabled,
+                        ..connection.clone()
+                    },
+                    _ => connection.clone(),
+                },
+            )
+            .collect();
+        Self {
+            connections,
+            ..self.clone()
+        }
+    }
+
+    /// Possibly perturbs the weights of the network, and possibly adds a connection or a node
+    pub fn mutate(&mut self, config: &NeatConfig, innovations: &mut Innovations, rng: &mut StdRng) {
+        let perturbation = Normal::new(0.0, config.weight_sigma.abs()).unwrap();
+        for connection in self.connections.iter_mut() {
+            if rng.gen_range(0.0, 1.0) < config.weight_mutation_rate {
+                connection.weight += perturbation.sample(rng);
+            }
+        }
+        if rng.gen_range(0.0, 1.0) < config.add_connection_rate {
+            self.add_connection(innovations, rng);
+        }
+        if rng.gen_range(0.0, 1.0) < config.add_node_rate {
+            self.add_node(innovations, rng);
+        }
+    }
+
+    /// Connects two nodes that aren't yet connected with a random weight, without creating a
+    /// cycle. Nothing changes if no such pair of nodes is found.
+    fn add_connection(&mut self, innovations: &mut Innovations, rng: &mut StdRng) {
+        let first_output = self.inputs as u64;
+        let first_hidden = (self.inputs + self.outputs) as u64;
+        let sources: Vec<u64> = (0..first_output)
+            .chain(self.hidden.iter().copied())
+            .collect();
+        let targets: Vec<u64> = (first_output..first_hidden)
+            .chain(self.hidden.iter().copied())
+            .collect();
+
+        for _ in 0..ADD_CONNECTION_ATTEMPTS {
+            let from = sources[rng.gen_range(0, sources.len())];
+            let to = targets[rng.gen_range(0, targets.len())];
+            let exists = self
+                .connections
+                .iter()
+                .any(|connection| connection.from == from && connection.to == to);
+            if from == to || exists || self.leads_to(to, from) {
+                continue;
+            }
+            let innovation = innovations.connection(from, to);
+            self.connections.push(ConnectionGene {
+                innovation,
+                from,
+                to,
+                weight: rng.gen_range(-1.0, 1.0),
+                enabled: true,
+            });
+            return;
+        }
+    }
+
+    /// Splits a random enabled connection with a new hidden node, which is connected to the
+    /// connection's ends with a weight of 1 into it and the old weight out of it, so that the
+    /// network behaves almost the same
+    fn add_node(&mut self, innovations: &mut Innovations, rng: &mut StdRng) {
+        let enabled: Vec<usize> = (0..self.connections.len())
+            .filter(|&index| self.connections[index].enabled)
+            .collect();
+        if enabled.is_empty() {
+            return;
+        }
+        let split = self.connections[enabled[rng.gen_range(0, enabled.len())]].clone();
+
+        let first_hidden = (self.inputs + self.outputs) as u64;
+        let node = innovations.split(split.innovation, first_hidden);
+        // The connection may have been split before and then re-enabled by crossover
+        if self.hidden.contains(&node) {
+            return;
+        }
+        let into = innovations.connection(split.from, node);
+        let out_of = innovations.connection(node, split.to);
+
+        for connection in self.connections.iter_mut() {
+            if connection.innovation == split.innovation {
+                connection.enabled = false;
+            }
+        }
+        self.hidden.push(node);
+        self.connections.push(ConnectionGene {
+            innovation: into,
+            from: split.from,
+            to: node,
+            weight: 1.0,
+            enabled: true,
+        });
+        self.connections.push(ConnectionGene {
+            innovation: out_of,
+            from: node,
+            to: split.to,
+            weight: split.weight,
+            enabled: true,
+        });
+    }
+
+    /// Returns whether or not there is a path of connections from one node to another
+    fn leads_to(&self, from: u64, to: u64) -> bool {
+        let mut stack = vec![from];
+        let mut visited = BTreeSet::new();
+        while let Some(id) = stack.pop() {
+            if id == to {
+                return true;
+            }
+            if visited.insert(id) {
+                stack.extend(
+                    self.connections
+                        .iter()
+                        .filter(|connection| connection.from == id)
+                        .map(|connection| connection.to),
+                );
+            }
+        }
+        false
+    }
+
+    /// Returns the compatibility distance between this network and another, from the numbers
+    /// of excess and disjoint connections and the mean weight difference of matching ones
+    pub fn distance(&self, other: &Genome, config: &NeatConfig) -> f32 {
+        let weights = |genome: &Genome| -> BTreeMap<u64, f32> {
+            genome
+                .connections
+                .iter()
+                .map(|connection| (connection.innovation, connection.weight))
+                .collect()
+        };
+        let (a, b) = (weights(self), weights(other));
+        let newest = |weights: &BTreeMap<u64, f32>| weights.keys().next_back().copied();
+        let (a_newest, b_newest) = (newest(&a), newest(&b));
+
+        let mut excess = 0;
+        let mut disjoint = 0;
+        let mut weight_difference = 0.0;
+        let mut matching = 0;
+        for (innovation, weight) in a.iter() {
+            match b.get(innovation) {
+                Some(other_weight) => {
+                    weight_difference += (weight - other_weight).abs();
+                    matching += 1;
+                }
+                None if Some(*innovation) > b_newest => excess += 1,
+                None => disjoint += 1,
+            }
+        }
+        for innovation in b.keys().filter(|innovation| !a.contains_key(innovation)) {
+            if Some(*innovation) > a_newest {
+                excess += 1;
+            } else {
+                disjoint += 1;
+            }
+        }
+
+        let mean_weight_difference = if matching > 0 {
+            weight_difference / matching as f32
+        } else {
+            0.0
+        };
+        config.excess_coefficient * excess as f32
+            + config.disjoint_coefficient * disjoint as f32
+            + config.weight_coefficient * mean_weight_difference
+    }
+
+    /// Returns the number of nodes in the network, including its inputs and outputs
+    pub fn node_count(&self) -> usize {
+        self.inputs + self.outputs + self.hidden.len()
+    }
+
+    /// Returns the number of enabled connections in the network
+    pub fn connection_count(&self) -> usize {
+        self.connections
+            .iter()
+            .filter(|connection| connection.enabled)
+            .count()
+    }
+}
+
+/// Testing that structural mutations keep the network acyclic and line up under crossover
+#[test]
+fn test_genome() {
+    use rand::SeedableRng;
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let config = NeatConfig {
+        add_connection_rate: 1.0,
+        add_node_rate: 1.0,
+        ..NeatConfig::default()
+    };
+    let mut innovations = Innovations::default();
+    let parent = Genome::new(3, 2, &mut innovations, &mut rng);
+    assert_eq!(parent.connection_count(), 6);
+
+    let mut child = parent.clone();
+    for _ in 0..10 {
+        child.mutate(&config, &mut innovations, &mut rng);
+    }
+    assert!(child.node_count() > parent.node_count());
+    assert!(child.evaluation_order().len() == child.node_count() - 3);
+    assert_eq!(child.activate(&[1.0, -0.5, 0.2]).len(), 2);
+
+    // Crossing over with a mate keeps the topology of the fitter parent
+    let offspring = child.crossover(&parent, &mut rng);
+    assert_eq!(offspring.node_count(), child.node_count());
+    assert_eq!(parent.distance(&parent, &config), 0.0);
+    assert!(child.distance(&parent, &config) > 0.0);
+
+    // Networks evolved in separate registries are numbered alike, whatever other networks were
+    // evolved before them
+    let evolve = |seed, innovations: &mut Innovations| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut genome = Genome::new(3, 2, innovations, &mut rng);
+        for _ in 0..10 {
+            genome.mutate(&config, innovations, &mut rng);
+        }
+        genome
+    };
+    let mut used = Innovations::default();
+    evolve(1, &mut used);
+    let fresh = evolve(2, &mut Innovations::default());
+    assert_eq!(evolve(2, &mut Innovations::default()), fresh);
+    assert_ne!(evolve(2, &mut used), fresh);
+}
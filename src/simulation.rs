@@ -0,0 +1,318 @@
+//! A module for running the simulation of the fish and food, independently of any window.
+
+use ggez::nalgebra::Point2;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{
+    arena::{Arena, Mask},
+    chart::History,
+    config::{Config, ConfigChanges},
+    error::{ConfigProblem, Error},
+    fish::Fish,
+    food::Food,
+    generation::next_generation,
+    neat::Innovations,
+    species::{Genotype, SpeciesTracker},
+    stats::Statistics,
+    world::{leaves_open_area, World},
+    Entity,
+};
+
+/// Every configuration and entity of the simulation
+pub struct Simulation {
+    /// The configuration that the simulation was created with, along with any changes that
+    /// were reloaded while it runs
+    pub config: Config,
+    /// Random number generator, which is seeded so that runs can be reproduced
+    rng: StdRng,
+    /// The space that the fish and food live in
+    pub world: World,
+    /// A collection of food
+    pub food: Vec<Food>,
+    /// A collection of fish groups who are organized based on their level in the food chain
+    pub fish_groups: Vec<Vec<Fish>>,
+    /// The fish of each group that died during the current generation, which are still scored
+    /// when the next generation is bred.
+    /// Only used in generational mode, as otherwise dead fish are simply removed.
+    graveyard: Vec<Vec<Fish>>,
+    /// The number of generations that have been bred in generational mode
+    pub generation: u64,
+    /// Every species that the fish have been clustered into, if speciation is enabled
+    pub species: SpeciesTracker,
+    /// The innovation numbers handed out to the NEAT networks of the fish
+    innovations: Innovations,
+    /// The number of ticks of the simulation that have been run
+    pub tick: u64,
+    /// The statistics of the most recent ticks, which are plotted in the charts
+    pub history: History,
+}
+
+impl Simulation {
+    /// Creates a new simulation in a world of the provided size, with an optional mask of the
+    /// arena's walls that has already been read.
+    /// Runs created with the same seed and configuration are identical.
+    pub fn new(config: Config, world_size: (f32, f32), mask: Option<Mask>, seed: u64) -> Self {
+        // Random number generator is used for psuedo-random components of this
+        // simulation
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let world = World::new(
+            world_size,
+            config.boundary_padding,
+            config.boundary_mode,
+            Arena::new(&config.arena, mask),
+        );
+
+        let mut food = Vec::new();
+        // Spawn the food
+        for _ in 1..config.food.quantity {
+            Self::add_food(&mut food, &world, &mut rng);
+        }
+
+        let mut fish_groups = Vec::new();
+        let mut innovations = Innovations::default();
+
+        let fish_per_group = config.fish.quantity / config.fish.total_food_chain_links;
+        // Spawn the fish
+        for group_index in 0..config.fish.total_food_chain_links {
+            fish_groups.push(Vec::new());
+            for _ in 0..fish_per_group {
+                fish_groups[group_index].push(Fish::new(
+                    &config.fish,
+                    &group_index,
+                    &world,
+                    &mut innovations,
+                    &mut rng,
+                ));
+            }
+        }
+
+        let history = History::new(config.chart_history);
+        let graveyard = fish_groups.iter().map(|_| Vec::new()).collect();
+
+        let mut simulation = Self {
+            config,
+            rng,
+            world,
+            food,
+            fish_groups,
+            graveyard,
+            generation: 0,
+            species: SpeciesTracker::default(),
+            innovations,
+            tick: 0,
+            history,
+        };
+        // The initial fish are clustered straight away, so that every fish has a species
+        simulation.speciate();
+        simulation
+    }
+
+    /// Returns the fish with the provided id, if it is still alive
+    pub fn find_fish(&self, id: u64) -> Option<&Fish> {
+        self.fish_groups
+            .iter()
+            .flatten()
+            .find(|fish| fish.id() == id)
+    }
+
+    /// Returns the fish whose radius the provided point in the world is within, if any.
+    /// If several fish overlap the point, the one whose center is nearest is returned.
+    pub fn fish_at(&self, pos: Point2<f32>) -> Option<&Fish> {
+        self.nearest_fish(pos)
+            .filter(|fish| self.world.distance(fish.pos(), pos) <= fish.radius())
+    }
+
+    /// Returns the fish nearest to the provided point in the world, if there are any fish
+    pub fn nearest_fish(&self, pos: Point2<f32>) -> Option<&Fish> {
+        self.fish_groups.iter().flatten().min_by(|a, b| {
+            let a_distance = self.world.distance(a.pos(), pos);
+            let b_distance = self.world.distance(b.pos(), pos);
+            a_distance.total_cmp(&b_distance)
+        })
+    }
+
+    /// Runs a single tick of the simulation, updating every piece of food and fish.
+    /// In generational mode, the next generation is bred once the current one has been
+    /// evaluated for `GenerationalConfig.generation_ticks`.
+    pub fn tick(&mut self) {
+        let generational = self.config.generational.is_some();
+
+        if self.rng.gen_bool(self.config.food.spawn_rate.into()) {
+            Self::add_food(&mut self.food, &self.world, &mut self.rng);
+        }
+
+        for group_index in 0..self.config.fish.total_food_chain_links {
+            let (prey, other_fish_groups) = self.fish_groups.split_at_mut(group_index);
+
+            let predator_positions = if group_index == self.config.fish.total_food_chain_links - 1 {
+                None
+            } else {
+                Some(
+                    other_fish_groups[1]
+                        .iter()
+                        .map(|predator| predator.pos())
+                        .collect(),
+                )
+            };
+
+            // The groups above this one hold its predators, whose juveniles may still be eaten
+            let (fish_group, larger) = other_fish_groups.split_first_mut().unwrap();
+
+            // We should remove dead fish from our collection of fish, although generational mode
+            // keeps them aside until the generation is scored
+            if generational {
+                let (alive, dead) = fish_group.drain(..).partition(|fish| fish.is_alive());
+                *fish_group = alive;
+                self.graveyard[group_index].extend(dead);
+            } else {
+                fish_group.retain(|fish| fish.is_alive());
+            }
+
+            let mut new_fish = None;
+
+            for fish in fish_group.iter_mut() {
+                // Only update living fish
+                if fish.is_alive() {
+                    // In generational mode, fish only reproduce between generations
+                    if !generational && new_fish.is_none() && self.rng.gen_ratio(1, 1000) {
+                        new_fish = Some(fish.clone(
+                            &mut self.rng,
+                            &self.config.fish,
+                            &mut self.innovations,
+                        ));
+                    }
+                    // Update the behavior state of all fish
+                    let eaten = fish.behave(
+                        &mut self.food,
+                        prey,
+                        larger,
+                        &predator_positions,
+                        &self.config.fish,
+                        &self.world,
+                    );
+                    if let (true, Some(eaten)) = (generational, eaten) {
+                        self.graveyard[eaten.group_index()].push(eaten);
+                    }
+                    // Update the physical state of all fish
+                    fish.update(self.config.fish.frames_per_animation_frame);
+                    // Bound the fish to the world after it has moved, so that wrapped or
+                    // reflected fish are never drawn outside of it
+                    fish.bound(&self.world);
+                }
+            }
+
+            if let Some(new_fish) = new_fish {
+                fish_group.push(new_fish)
+            };
+        }
+
+        self.history.record(Statistics::new(&self.fish_groups));
+        self.tick += 1;
+
+        if let Some(generational) = &self.config.generational {
+            if self.tick.is_multiple_of(generational.generation_ticks) {
+                self.next_generation();
+            }
+        }
+        if let Some(speciation) = &self.config.speciation {
+            if self.tick.is_multiple_of(speciation.interval) {
+                self.speciate();
+            }
+        }
+    }
+
+    /// Clusters the fish of each group into species by genetic distance, if speciation is
+    /// enabled
+    fn speciate(&mut self) {
+        let speciation = match &self.config.speciation {
+            Some(speciation) => speciation,
+            None => return,
+        };
+        for (group_index, fish_group) in self.fish_groups.iter_mut().enumerate() {
+            let genotypes: Vec<Genotype> = fish_group.iter().map(Genotype::of).collect();
+            let ids = self.species.assign(
+                group_index,
+                &genotypes,
+                &self.config.fish,
+                speciation.compatibility_threshold,
+                self.tick,
+            );
+            for (fish, id) in fish_group.iter_mut().zip(ids) {
+                fish.set_species(id);
+            }
+        }
+    }
+
+    /// Replaces every fish group with the offspring of the fish that lived during the current
+    /// generation, and restocks the food so that every generation starts out alike
+    fn next_generation(&mut self) {
+        let generational = match &self.config.generational {
+            Some(generational) => generational,
+            None => return,
+        };
+        let within_species = match &self.config.speciation {
+            Some(speciation) => speciation.within_species_mating,
+            None => false,
+        };
+        for (fish_group, dead) in self.fish_groups.iter_mut().zip(self.graveyard.iter_mut()) {
+            let mut population = std::mem::take(fish_group);
+            population.append(dead);
+            *fish_group = next_generation(
+                population,
+                generational,
+                &self.config.fish,
+                within_species,
+                &self.world,
+                &mut self.innovations,
+                &mut self.rng,
+            );
+        }
+
+        self.food.clear();
+        for _ in 1..self.config.food.quantity {
+            Self::add_food(&mut self.food, &self.world, &mut self.rng);
+        }
+        self.generation += 1;
+    }
+
+    /// Applies the fields of the provided configuration that can be changed while the simulation
+    /// runs, and reports which other fields differ and need a restart.
+    /// The configuration is rejected if its boundary padding would cover the whole world, whose
+    /// size may differ from the configured one once the window has been resized.
+    pub fn reload_config(&mut self, reloaded: &Config) -> Result<ConfigChanges, Error> {
+        let (width, height) = self.world.size;
+        if !leaves_open_area(self.world.size, reloaded.boundary_padding) {
+            return Err(Error::InvalidConfig(vec![ConfigProblem {
+                path: "boundary_padding".to_string(),
+                problem: format!(
+                    "a padding of {} on each side covers the whole {}x{} world",
+                    reloaded.boundary_padding, width, height
+                ),
+                suggestion: format!("use a padding below {}", width.min(height) / 2.0),
+            }]));
+        }
+        let changes = self.config.reload(reloaded)?;
+        // The world keeps its own copy of the fields that describe its geometry
+        self.world.boundary_padding = self.config.boundary_padding;
+        self.world.arena.wall_avoidance_distance = self.config.arena.wall_avoidance_distance;
+        self.world.arena.walls_block_sight = self.config.arena.walls_block_sight;
+        Ok(changes)
+    }
+
+    /// Resizes the world, removing the food that is no longer within it
+    pub fn resize_world(&mut self, size: (f32, f32)) {
+        self.world.size = size;
+        // Food that is now outside of the world can never be reached
+        self.food.retain(|food| {
+            food.pos.x >= 0.0 && food.pos.x <= size.0 && food.pos.y >= 0.0 && food.pos.y <= size.1
+        });
+    }
+
+    /// Adds a peice of food to the collection, outside of the walls of the arena
+    fn add_food(food: &mut Vec<Food>, world: &World, rng: &mut StdRng) {
+        if let Some(pos) = world.random_open_position(rng) {
+            food.push(Food::new(pos));
+        }
+    }
+}
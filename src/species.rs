@@ -0,0 +1,207 @@
+//! A module for clustering the fish of each food-chain link into species by genetic distance,
+//! and tracking when each species is born and goes extinct.
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    controller::Brain,
+    dna::GENE_COUNT,
+    fish::{Fish, FishConfig},
+};
+
+/// The configuration of speciation that is read and deserialized from `config.ron`.
+/// Any field that is left out takes its value from `SpeciationConfig::default()`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SpeciationConfig {
+    /// The number of ticks between each time the fish are clustered into species
+    pub interval: u64,
+    /// The largest genetic distance from a species' representative at which a fish still
+    /// belongs to that species, where the distance is the mean difference between genes as a
+    /// fraction of the width of their bounds, plus the compatibility distance between their
+    /// networks if the fish are controlled by NEAT networks
+    pub compatibility_threshold: f32,
+    /// Whether or not offspring can only be bred from two parents of the same species.
+    /// Only fish bred in generational mode have two parents.
+    pub within_species_mating: bool,
+}
+
+impl Default for SpeciationConfig {
+    fn default() -> Self {
+        Self {
+            interval: 100,
+            compatibility_threshold: 0.15,
+            within_species_mating: false,
+        }
+    }
+}
+
+/// Everything about a fish that it is compared by when it is clustered into a species
+#[derive(Debug, Clone)]
+pub struct Genotype {
+    /// The DNA of the fish
+    pub dna: [f32; GENE_COUNT],
+    /// The brain of the fish
+    pub brain: Brain,
+}
+
+impl Genotype {
+    /// Returns the genotype of the provided fish
+    pub fn of(fish: &Fish) -> Self {
+        Self {
+            dna: *fish.dna(),
+            brain: fish.brain().clone(),
+        }
+    }
+
+    /// Returns the distance between this genotype and another, which is the distance between
+    /// their DNA plus the distance between their brains
+    pub fn distance(&self, other: &Genotype, fish_config: &FishConfig) -> f32 {
+        fish_config.genes.distance(&self.dna, &other.dna)
+            + self.brain.distance(&other.brain, &fish_config.controller)
+    }
+}
+
+/// A cluster of genetically similar fish within a single food-chain link
+#[derive(Debug, Clone)]
+pub struct Species {
+    /// The unique id of the species
+    pub id: u64,
+    /// The index of the fish group that the species belongs to
+    pub group_index: usize,
+    /// The genotype that fish are compared against to decide whether they belong to the
+    /// species, which is that of its oldest member as of the last clustering
+    pub representative: Genotype,
+    /// The tick at which the species was first found
+    pub born: u64,
+    /// The number of fish in the species as of the last clustering
+    pub size: usize,
+    /// The largest number of fish that the species has had
+    pub peak_size: usize,
+    /// The tick of the clustering at which the species no longer had any fish, if it has died out
+    pub extinct: Option<u64>,
+}
+
+/// Every species that has lived in the simulation
+#[derive(Debug, Default)]
+pub struct SpeciesTracker {
+    /// The id that will be given to the next species that is found
+    next_id: u64,
+    /// Every species that has been found, including those that have gone extinct
+    pub species: Vec<Species>,
+}
+
+impl SpeciesTracker {
+    /// Assigns each of the provided genotypes of a fish group to a species, and returns the id
+    /// of the species of each one in the same order.
+    ///
+    /// Each genotype joins the first living species of the group whose representative is within
+    /// the compatibility threshold, or else founds a new species that it represents. Species
+    /// that are left without any fish go extinct, and every other species is then represented by
+    /// its first member.
+    pub fn assign(
+        &mut self,
+        group_index: usize,
+        genotypes: &[Genotype],
+        fish_config: &FishConfig,
+        compatibility_threshold: f32,
+        tick: u64,
+    ) -> Vec<u64> {
+        let mut members: Vec<(usize, Vec<usize>)> = self
+            .species
+            .iter()
+            .enumerate()
+            .filter(|(_, species)| species.group_index == group_index && species.extinct.is_none())
+            .map(|(species_index, _)| (species_index, Vec::new()))
+            .collect();
+
+        let mut ids = Vec::with_capacity(genotypes.len());
+        for (genotype_index, genotype) in genotypes.iter().enumerate() {
+            let compatible = members.iter_mut().find(|(species_index, _)| {
+                genotype.distance(&self.species[*species_index].representative, fish_config)
+                    <= compatibility_threshold
+            });
+            let species_index = match compatible {
+                Some((species_index, species_members)) => {
+                    species_members.push(genotype_index);
+                    *species_index
+                }
+                None => {
+                    self.species.push(Species {
+                        id: self.next_id,
+                        group_index,
+                        representative: genotype.clone(),
+                        born: tick,
+                        size: 0,
+                        peak_size: 0,
+                        extinct: None,
+                    });
+                    self.next_id += 1;
+                    members.push((self.species.len() - 1, vec![genotype_index]));
+                    self.species.len() - 1
+                }
+            };
+            ids.push(self.species[species_index].id);
+        }
+
+        for (species_index, species_members) in members {
+            let species = &mut self.species[species_index];
+            species.size = species_members.len();
+            species.peak_size = species.peak_size.max(species.size);
+            match species_members.first() {
+                Some(&first) => species.representative = genotypes[first].clone(),
+                None => species.extinct = Some(tick),
+            }
+        }
+        ids
+    }
+
+    /// Returns the number of species that are still alive
+    pub fn living(&self) -> usize {
+        self.species
+            .iter()
+            .filter(|species| species.extinct.is_none())
+            .count()
+    }
+
+    /// Returns the number of species that have gone extinct
+    pub fn extinct(&self) -> usize {
+        self.species.len() - self.living()
+    }
+
+    /// Returns the lines of text describing the species, to be displayed with the statistics
+    pub fn lines(&self) -> Vec<String> {
+        vec![format!(
+            "Species: {} ({} born, {} extinct)",
+            self.living(),
+            self.species.len(),
+            self.extinct()
+        )]
+    }
+}
+
+/// Testing that distant genomes found new species and that empty species go extinct
+#[test]
+fn test_assign() {
+    let fish_config = FishConfig::default();
+    let mut tracker = SpeciesTracker::default();
+
+    let genotype = |dna| Genotype {
+        dna,
+        brain: Brain::Weights,
+    };
+    let near = genotype([0.0, 0.0, 50.0, 50.0]);
+    let also_near = genotype([0.1, 0.0, 50.0, 52.0]);
+    let far = genotype([4.0, -4.0, 250.0, 10.0]);
+    let group = [near.clone(), also_near, far.clone()];
+    assert_eq!(tracker.assign(0, &group, &fish_config, 0.15, 0), [0, 0, 1]);
+    // Species of other groups are kept apart
+    assert_eq!(tracker.assign(1, &[near], &fish_config, 0.15, 0), [2]);
+
+    let ids = tracker.assign(0, &[far.clone(), far], &fish_config, 0.15, 100);
+    assert_eq!(ids, [1, 1]);
+    assert_eq!(tracker.species[0].extinct, Some(100));
+    assert_eq!(tracker.species[1].size, 2);
+    assert_eq!(tracker.species[1].peak_size, 2);
+    assert_eq!((tracker.living(), tracker.extinct()), (2, 1));
+}
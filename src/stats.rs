@@ -0,0 +1,220 @@
+//! A module for summarizing the state of the fish population.
+
+use super::{
+    dna::{GenesConfig, GENE_COUNT, GENE_NAMES},
+    fish::Fish,
+};
+
+/// A summary of the fish population at a moment in the simulation
+#[derive(Debug, Clone)]
+pub struct Statistics {
+    /// The number of fish in each group, ordered by their level in the food chain
+    pub group_populations: Vec<usize>,
+    /// The mean value of each gene across every fish, in the order they are stored in the DNA
+    pub gene_means: [f32; GENE_COUNT],
+    /// The mean mutation rate carried by every fish
+    pub mean_mutation_rate: f32,
+    /// The mean mutation step size multiplier carried by every fish
+    pub mean_mutation_step: f32,
+}
+
+impl Statistics {
+    /// Summarizes the provided fish groups
+    pub fn new(fish_groups: &[Vec<Fish>]) -> Self {
+        let group_populations: Vec<usize> = fish_groups.iter().map(Vec::len).collect();
+        let total: usize = group_populations.iter().sum();
+
+        let mut gene_means = [0.0; GENE_COUNT];
+        let mut mean_mutation_rate = 0.0;
+        let mut mean_mutation_step = 0.0;
+
+        if total > 0 {
+            for fish in fish_groups.iter().flatten() {
+                for (mean, gene) in gene_means.iter_mut().zip(fish.dna().iter()) {
+                    *mean += gene;
+                }
+                mean_mutation_rate += fish.mutation_rate();
+                mean_mutation_step += fish.mutation_step();
+            }
+            for mean in gene_means.iter_mut() {
+                *mean /= total as f32;
+            }
+            mean_mutation_rate /= total as f32;
+            mean_mutation_step /= total as f32;
+        }
+
+        Self {
+            group_populations,
+            gene_means,
+            mean_mutation_rate,
+            mean_mutation_step,
+        }
+    }
+
+    /// Returns the total number of fish
+    pub fn population(&self) -> usize {
+        self.group_populations.iter().sum()
+    }
+
+    /// Returns the header of a CSV table of the statistics of a population with the provided
+    /// number of fish groups, as written by `Statistics::csv_row`
+    pub fn csv_header(group_count: usize) -> String {
+        let mut columns = vec!["tick".to_string(), "population".to_string()];
+        columns.extend((0..group_count).map(|group_index| format!("group_{}", group_index)));
+        columns.extend(GENE_NAMES.iter().map(|name| format!("mean_{}", name)));
+        columns.push("mean_mutation_rate".to_string());
+        columns.push("mean_mutation_step".to_string());
+        columns.join(",")
+    }
+
+    /// Returns the statistics of the provided tick as a row of a CSV table
+    pub fn csv_row(&self, tick: u64) -> String {
+        let mut columns = vec![tick.to_string(), self.population().to_string()];
+        columns.extend(self.group_populations.iter().map(usize::to_string));
+        columns.extend(self.gene_means.iter().map(f32::to_string));
+        columns.push(self.mean_mutation_rate.to_string());
+        columns.push(self.mean_mutation_step.to_string());
+        columns.join(",")
+    }
+
+    /// Returns the statistics as lines of text to be displayed
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "Population: {} {:?}",
+            self.population(),
+            self.group_populations
+        )];
+        for (name, mean) in GENE_NAMES.iter().zip(self.gene_means.iter()) {
+            lines.push(format!("Mean {}: {:.3}", name, mean));
+        }
+        lines.push(format!(
+            "Mean mutation rate: {:.4}",
+            self.mean_mutation_rate
+        ));
+        lines.push(format!(
+            "Mean mutation step: {:.3}",
+            self.mean_mutation_step
+        ));
+        lines
+    }
+}
+
+/// The allele frequencies of the diploid fish population, where each allele of a gene is counted
+/// as either a high allele, at or above the midpoint of the gene's initial range, or a low one
+#[derive(Debug, Clone)]
+pub struct AlleleFrequencies {
+    /// The frequency of the high allele of each gene, in the order they are stored in the DNA
+    pub high: [f32; GENE_COUNT],
+    /// The fraction of fish that carry one high and one low allele of each gene
+    pub heterozygosity: [f32; GENE_COUNT],
+}
+
+impl AlleleFrequencies {
+    /// Summarizes the alleles of the provided fish groups, or returns nothing if none of the fish
+    /// are diploid
+    pub fn new(fish_groups: &[Vec<Fish>], genes: &GenesConfig) -> Option<Self> {
+        let midpoints: Vec<f32> = genes
+            .as_array()
+            .iter()
+            .map(|gene_config| gene_config.allele_midpoint())
+            .collect();
+
+        let mut high = [0.0; GENE_COUNT];
+        let mut heterozygosity = [0.0; GENE_COUNT];
+        let mut total = 0;
+        for alleles in fish_groups.iter().flatten().filter_map(Fish::alleles) {
+            for gene_index in 0..GENE_COUNT {
+                let is_high = |allele_set: &[f32; GENE_COUNT]| {
+                    allele_set[gene_index] >= midpoints[gene_index]
+                };
+                let high_count = alleles.iter().filter(|set| is_high(set)).count();
+                high[gene_index] += high_count as f32;
+                if high_count == 1 {
+                    heterozygosity[gene_index] += 1.0;
+                }
+            }
+            total += 1;
+        }
+        if total == 0 {
+            return None;
+        }
+
+        for (frequency, heterozygous) in high.iter_mut().zip(heterozygosity.iter_mut()) {
+            *frequency /= 2.0 * total as f32;
+            *heterozygous /= total as f32;
+        }
+        Some(Self {
+            high,
+            heterozygosity,
+        })
+    }
+
+    /// Returns the allele frequencies as lines of text to be displayed, where the observed
+    /// heterozygosity of each gene is shown next to the 2pq expected under Hardy-Weinberg
+    /// equilibrium
+    pub fn lines(&self) -> Vec<String> {
+        GENE_NAMES
+            .iter()
+            .zip(self.high.iter().zip(self.heterozygosity.iter()))
+            .map(|(name, (p, heterozygosity))| {
+                format!(
+                    "Allele {}: p {:.2}, het {:.2} (2pq {:.2})",
+                    name,
+                    p,
+                    heterozygosity,
+                    2.0 * p * (1.0 - p)
+                )
+            })
+            .collect()
+    }
+}
+
+/// Testing that the allele frequencies count the high alleles and the heterozygotes of each gene
+/// across every group
+#[test]
+fn test_allele_frequencies() {
+    use super::{
+        arena::Arena,
+        neat::Innovations,
+        world::{BoundaryMode, World},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let world = World::new((100.0, 50.0), 0.0, BoundaryMode::Wrap, Arena::default());
+    let fish_config = super::fish::FishConfig {
+        diploid: true,
+        ..Default::default()
+    };
+    // Homozygous fish, whose alleles are all drawn from either the high or the low half of each
+    // gene's initial range
+    let mut homozygous = |high: bool| {
+        let mut config = fish_config.clone();
+        let genes = &mut config.genes;
+        for gene in [
+            &mut genes.food_weight,
+            &mut genes.predator_weight,
+            &mut genes.food_perception,
+            &mut genes.predator_perception,
+        ]
+        .iter_mut()
+        {
+            let midpoint = gene.allele_midpoint();
+            gene.initial_range = if high {
+                (midpoint, gene.initial_range.1)
+            } else {
+                (gene.initial_range.0, midpoint)
+            };
+        }
+        Fish::new(&config, &0, &world, &mut Innovations::default(), &mut rng)
+    };
+    let fish_groups = vec![
+        vec![homozygous(true), homozygous(false)],
+        vec![homozygous(true)],
+    ];
+
+    let frequencies = AlleleFrequencies::new(&fish_groups, &fish_config.genes).unwrap();
+    assert_eq!(frequencies.high, [2.0 / 3.0; GENE_COUNT]);
+    assert_eq!(frequencies.heterozygosity, [0.0; GENE_COUNT]);
+    assert!(AlleleFrequencies::new(&[], &fish_config.genes).is_none());
+}
@@ -0,0 +1,300 @@
+//! A module for running batches of headless simulations over a grid of configurations.
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    arena::Mask, config::Config, dna::GENE_NAMES, error::Error, simulation::Simulation,
+    stats::Statistics,
+};
+
+/// The specification of a sweep, which is read from a RON, TOML or JSON file.
+/// Every combination of the values of the parameters is run once per seed.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SweepSpec {
+    /// The number of ticks that each run lasts, unless every fish dies before then
+    pub ticks: u64,
+    /// The number of runs of each combination of parameters, each with its own seed
+    #[serde(default = "default_seeds")]
+    pub seeds: u64,
+    /// The seed of the first run of each combination, which is counted up from for the others
+    #[serde(default)]
+    pub first_seed: u64,
+    /// The fields of the configuration that are swept
+    pub parameters: Vec<SweepParameter>,
+}
+
+/// A field of the configuration and the values it takes in a sweep
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SweepParameter {
+    /// The path of the field, such as `fish.mutation_rate`
+    pub field: String,
+    /// The values of the field, written the same way as for `Config::apply_override`,
+    /// such as `"0.05"` or `"Wrap"`
+    pub values: Vec<String>,
+}
+
+/// Returns the number of runs of each combination when none is provided
+fn default_seeds() -> u64 {
+    1
+}
+
+impl SweepSpec {
+    /// Returns every combination of the values of the parameters, as assignments of the form
+    /// `path.to.field=value`
+    pub fn combinations(&self) -> Vec<Vec<String>> {
+        self.parameters
+            .iter()
+            .fold(vec![Vec::new()], |combinations, parameter| {
+                combinations
+                    .iter()
+                    .flat_map(|combination| {
+                        parameter.values.iter().map(move |value| {
+                            let mut combination = combination.clone();
+                            combination.push(format!("{}={}", parameter.field, value));
+                            combination
+                        })
+                    })
+                    .collect()
+            })
+    }
+
+    /// Returns the total number of runs in the sweep
+    pub fn run_count(&self) -> usize {
+        self.combinations().len() * self.seeds as usize
+    }
+}
+
+/// The outcome of a single run of a sweep
+#[derive(Debug, Clone)]
+pub struct RunSummary {
+    /// The assignments of the swept fields in this run
+    pub assignments: Vec<String>,
+    /// The seed of the run
+    pub seed: u64,
+    /// The number of ticks that were run, which is fewer than the sweep's if every fish died
+    pub ticks: u64,
+    /// The statistics of the population at the end of the run
+    pub statistics: Statistics,
+    /// The tick at which each fish group died out, if it did
+    pub extinction_ticks: Vec<Option<u64>>,
+}
+
+/// Runs every combination of the sweep once per seed on top of the base configuration, with
+/// the mask of the arena's walls of each run's configuration read by `read_mask`.
+/// `on_run` is called with the summary of each run as soon as it finishes.
+pub fn run_sweep(
+    spec: &SweepSpec,
+    base: &Config,
+    read_mask: impl Fn(&Config) -> Result<Option<Mask>, Error>,
+    mut on_run: impl FnMut(&RunSummary),
+) -> Result<Vec<RunSummary>, Error> {
+    let mut summaries = Vec::new();
+    for assignments in spec.combinations() {
+        let mut config = base.clone();
+        for assignment in assignments.iter() {
+            config.apply_override(assignment)?;
+        }
+        config.validate()?;
+        let mask = read_mask(&config)?;
+
+        for seed in spec.first_seed..spec.first_seed + spec.seeds {
+            let world_size = config.headless_world_size();
+            let simulation = Simulation::new(config.clone(), world_size, mask.clone(), seed);
+            let summary = run(simulation, assignments.clone(), seed, spec.ticks);
+            on_run(&summary);
+            summaries.push(summary);
+        }
+    }
+    Ok(summaries)
+}
+
+/// Runs a single simulation for the provided number of ticks, or until every fish has died
+fn run(mut simulation: Simulation, assignments: Vec<String>, seed: u64, ticks: u64) -> RunSummary {
+    let mut extinction_ticks = vec![None; simulation.fish_groups.len()];
+    while simulation.tick < ticks {
+        simulation.tick();
+        for (extinction_tick, fish_group) in extinction_ticks
+            .iter_mut()
+            .zip(simulation.fish_groups.iter())
+        {
+            if extinction_tick.is_none() && fish_group.is_empty() {
+                *extinction_tick = Some(simulation.tick);
+            }
+        }
+        if extinction_ticks.iter().all(Option::is_some) {
+            break;
+        }
+    }
+
+    RunSummary {
+        assignments,
+        seed,
+        ticks: simulation.tick,
+        statistics: Statistics::new(&simulation.fish_groups),
+        extinction_ticks,
+    }
+}
+
+/// Returns the summaries of the runs as the rows of a table, with a header row first.
+/// Fish groups that didn't die out have a `-` as their extinction tick.
+pub fn summary_table(spec: &SweepSpec, summaries: &[RunSummary]) -> Vec<Vec<String>> {
+    let group_count = summaries
+        .iter()
+        .map(|summary| summary.extinction_ticks.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut header = vec!["run".to_string()];
+    header.extend(
+        spec.parameters
+            .iter()
+            .map(|parameter| parameter.field.clone()),
+    );
+    header.extend(vec![
+        "seed".to_string(),
+        "ticks".to_string(),
+        "population".to_string(),
+    ]);
+    header.extend((0..group_count).map(|group_index| format!("group_{}", group_index)));
+    header.extend((0..group_count).map(|group_index| format!("extinct_{}", group_index)));
+    header.extend(GENE_NAMES.iter().map(|name| format!("mean_{}", name)));
+
+    let mut rows = vec![header];
+    for (run_index, summary) in summaries.iter().enumerate() {
+        let mut row = vec![run_index.to_string()];
+        row.extend(summary.assignments.iter().map(|assignment| {
+            let (_, value) = assignment.split_once('=').unwrap_or_default();
+            value.to_string()
+        }));
+        row.push(summary.seed.to_string());
+        row.push(summary.ticks.to_string());
+        row.push(summary.statistics.population().to_string());
+        for group_index in 0..group_count {
+            let population = summary.statistics.group_populations.get(group_index);
+            row.push(population.map_or("-".to_string(), usize::to_string));
+        }
+        for group_index in 0..group_count {
+            let extinction_tick = summary.extinction_ticks.get(group_index).cloned().flatten();
+            row.push(extinction_tick.map_or("-".to_string(), |tick| tick.to_string()));
+        }
+        row.extend(
+            summary
+                .statistics
+                .gene_means
+                .iter()
+                .map(|mean| format!("{:.4}", mean)),
+        );
+        rows.push(row);
+    }
+    rows
+}
+
+/// Returns the rows of a table with their cells padded into aligned columns
+pub fn format_table(rows: &[Vec<String>]) -> String {
+    let column_count = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..column_count)
+        .map(|column| {
+            rows.iter()
+                .filter_map(|row| row.get(column))
+                .map(String::len)
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<String>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Returns the rows of a table as CSV, where cells that contain a comma, a quote or a line break
+/// are quoted, such as the values of tuple fields
+pub fn format_csv(rows: &[Vec<String>]) -> String {
+    rows.iter()
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|cell| {
+                    if cell.contains([',', '"', '\n', '\r']) {
+                        format!("\"{}\"", cell.replace('"', "\"\""))
+                    } else {
+                        cell.clone()
+                    }
+                })
+                .collect();
+            cells.join(",") + "\n"
+        })
+        .collect()
+}
+
+/// Testing that every combination is run once per seed, and that runs are reproducible
+#[test]
+fn test_run_sweep() {
+    let spec = SweepSpec {
+        ticks: 5,
+        seeds: 2,
+        first_seed: 10,
+        parameters: vec![
+            SweepParameter {
+                field: "fish.mutation_rate".to_string(),
+                values: vec!["0.01".to_string(), "0.1".to_string()],
+            },
+            SweepParameter {
+                field: "boundary_mode".to_string(),
+                values: vec!["Steer".to_string(), "Wrap".to_string()],
+            },
+        ],
+    };
+    assert_eq!(spec.run_count(), 8);
+
+    let mut base = Config::default();
+    base.fish.quantity = 6;
+    base.food.quantity = 10;
+    let summaries = run_sweep(&spec, &base, |_| Ok(None), |_| ()).unwrap();
+    assert_eq!(summaries.len(), 8);
+    assert_eq!(
+        summaries[2].assignments,
+        ["fish.mutation_rate=0.01", "boundary_mode=Wrap"]
+    );
+    assert_eq!(summaries[2].seed, 10);
+
+    let table = summary_table(&spec, &summaries);
+    assert_eq!(table.len(), 9);
+    assert_eq!(table[3][1..4], ["0.01", "Wrap", "10"]);
+
+    // The same seed and combination always gives the same outcome
+    let again = run_sweep(&spec, &base, |_| Ok(None), |_| ()).unwrap();
+    assert_eq!(
+        summary_table(&spec, &again)[1..],
+        summary_table(&spec, &summaries)[1..]
+    );
+
+    // The values of tuple fields are quoted so that they stay within a single CSV cell
+    let spec = SweepSpec {
+        ticks: 1,
+        seeds: 1,
+        first_seed: 0,
+        parameters: vec![SweepParameter {
+            field: "fish.scale_range".to_string(),
+            values: vec!["[1.0, 2.0]".to_string()],
+        }],
+    };
+    let summaries = run_sweep(&spec, &base, |_| Ok(None), |_| ()).unwrap();
+    let table = summary_table(&spec, &summaries);
+    let csv = format_csv(&table);
+    let row = csv.lines().nth(1).unwrap();
+    assert!(row.starts_with("0,\"[1.0, 2.0]\",0,"), "{}", row);
+    let header_columns = csv.lines().next().unwrap().split(',').count();
+    assert_eq!(
+        row.replace("\"[1.0, 2.0]\"", "").split(',').count(),
+        header_columns
+    );
+}
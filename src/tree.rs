@@ -0,0 +1,377 @@
+//! A module for behaviour trees that are evolved by genetic programming, where a fish's whole
+//! strategy is a tree of primitive behaviours and conditions.
+
+use ggez::nalgebra::Vector2;
+use rand::{rngs::StdRng, Rng};
+use rand_distr::{Distribution, Normal};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::controller::Senses;
+
+/// The configuration of the behaviour tree controller that is read and deserialized from
+/// `config.ron`.
+/// Any field that is left out takes its value from `TreeConfig::default()`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TreeConfig {
+    /// The deepest that a tree can grow, where a tree of a single behaviour has a depth of 1.
+    /// Newly spawned fish have trees of up to this depth.
+    pub max_depth: usize,
+    /// The chance of an offspring's tree having a random subtree replaced by a new random one
+    pub subtree_mutation_rate: f32,
+    /// The chance of each threshold, radius and turn in an offspring's tree being perturbed
+    pub constant_mutation_rate: f32,
+    /// The standard deviation of the normally distributed perturbation of a constant
+    pub constant_sigma: f32,
+    /// The distance between two trees per node that differs between them, which is added to the
+    /// distance used by speciation
+    pub distance_coefficient: f32,
+}
+
+impl Default for TreeConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            subtree_mutation_rate: 0.1,
+            constant_mutation_rate: 0.2,
+            constant_sigma: 0.1,
+            distance_coefficient: 0.05,
+        }
+    }
+}
+
+/// A node of a behaviour tree, which returns a steering force as a fraction of the fish's
+/// maximum steering force
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    /// Steers towards what the fish is heading towards to eat, if anything
+    SeekFood,
+    /// Steers away from the nearest perceived predator, if any
+    FleePredator,
+    /// Steers in the direction of the fish's velocity turned by the provided angle in radians
+    Wander { turn: f32 },
+    /// Returns the first child's steering force if the fish's health is below the threshold,
+    /// and the second's otherwise
+    IfHealthBelow {
+        threshold: f32,
+        then: Box<Node>,
+        otherwise: Box<Node>,
+    },
+    /// Returns the first child's steering force if a predator is perceived within the provided
+    /// fraction of the fish's predator perception, and the second's otherwise
+    IfPredatorWithin {
+        radius: f32,
+        then: Box<Node>,
+        otherwise: Box<Node>,
+    },
+    /// Returns the sum of the steering forces of both children
+    Both(Box<Node>, Box<Node>),
+}
+
+impl Node {
+    /// Returns a random tree of at most the provided depth, where each branch stops growing
+    /// early with an even chance
+    pub fn random(max_depth: usize, rng: &mut StdRng) -> Self {
+        if max_depth <= 1 || rng.gen_bool(0.5) {
+            return match rng.gen_range(0, 3) {
+                0 => Node::SeekFood,
+                1 => Node::FleePredator,
+                _ => Node::Wander {
+                    turn: rng.gen_range(-1.0, 1.0),
+                },
+            };
+        }
+        let mut child = || Box::new(Node::random(max_depth - 1, rng));
+        let (then, otherwise) = (child(), child());
+        match rng.gen_range(0, 3) {
+            0 => Node::IfHealthBelow {
+                threshold: rng.gen_range(0.0, 1.0),
+                then,
+                otherwise,
+            },
+            1 => Node::IfPredatorWithin {
+                radius: rng.gen_range(0.0, 1.0),
+                then,
+                otherwise,
+            },
+            _ => Node::Both(then, otherwise),
+        }
+    }
+
+    /// Returns the steering force of the tree for what the fish senses
+    pub fn steer(&self, senses: &Senses) -> Vector2<f32> {
+        let direction = |offset: Option<Vector2<f32>>| match offset {
+            Some(offset) if offset != Vector2::new(0.0, 0.0) => offset.normalize(),
+            _ => Vector2::new(0.0, 0.0),
+        };
+        match self {
+            Node::SeekFood => direction(senses.target),
+            Node::FleePredator => -direction(senses.predator),
+            Node::Wander { turn } => {
+                let heading = if senses.velocity == Vector2::new(0.0, 0.0) {
+                    0.0
+                } else {
+                    senses.velocity.y.atan2(senses.velocity.x)
+                };
+                Vector2::new((heading + turn).cos(), (heading + turn).sin())
+            }
+            Node::IfHealthBelow {
+                threshold,
+                then,
+                otherwise,
+            } => {
+                if senses.health < *threshold {
+                    then.steer(senses)
+                } else {
+                    otherwise.steer(senses)
+                }
+            }
+            Node::IfPredatorWithin {
+                radius,
+                then,
+                otherwise,
+            } => match senses.predator {
+                Some(predator) if predator.magnitude() <= *radius => then.steer(senses),
+                _ => otherwise.steer(senses),
+            },
+            Node::Both(a, b) => a.steer(senses) + b.steer(senses),
+        }
+    }
+
+    /// Returns the children of the node
+    fn children(&self) -> Vec<&Node> {
+        match self {
+            Node::IfHealthBelow {
+                then, otherwise, ..
+            }
+            | Node::IfPredatorWithin {
+                then, otherwise, ..
+            }
+            | Node::Both(then, otherwise) => vec![then, otherwise],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns mutable references to the children of the node
+    fn children_mut(&mut self) -> Vec<&mut Node> {
+        match self {
+            Node::IfHealthBelow {
+                then, otherwise, ..
+            }
+            | Node::IfPredatorWithin {
+                then, otherwise, ..
+            }
+            | Node::Both(then, otherwise) => vec![then, otherwise],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the number of nodes in the tree
+    pub fn size(&self) -> usize {
+        1 + self
+            .children()
+            .iter()
+            .map(|child| child.size())
+            .sum::<usize>()
+    }
+
+    /// Returns the depth of the tree, where a single node has a depth of 1
+    pub fn depth(&self) -> usize {
+        1 + self
+            .children()
+            .iter()
+            .map(|child| child.depth())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the subtree at the provided index, where nodes are numbered in pre-order
+    fn subtree(&self, index: usize) -> Option<&Node> {
+        if index == 0 {
+            return Some(self);
+        }
+        let mut index = index - 1;
+        for child in self.children() {
+            let size = child.size();
+            if index < size {
+                return child.subtree(index);
+            }
+            index -= size;
+        }
+        None
+    }
+
+    /// Returns the subtree at the provided index mutably, where nodes are numbered in pre-order
+    fn subtree_mut(&mut self, index: usize) -> Option<&mut Node> {
+        if index == 0 {
+            return Some(self);
+        }
+        let mut index = index - 1;
+        for child in self.children_mut() {
+            let size = child.size();
+            if index < size {
+                return child.subtree_mut(index);
+            }
+            index -= size;
+        }
+        None
+    }
+
+    /// Returns the offspring of this tree and a mate, where a random subtree of this tree is
+    /// replaced by a random subtree of the mate's. This tree is copied unchanged if the
+    /// offspring would be deeper than the provided maximum depth.
+    pub fn crossover(&self, mate: &Node, max_depth: usize, rng: &mut StdRng) -> Self {
+        let mut offspring = self.clone();
+        let donated = mate.subtree(rng.gen_range(0, mate.size()));
+        let replaced = offspring.subtree_mut(rng.gen_range(0, self.size()));
+        if let (Some(donated), Some(replaced)) = (donated, replaced) {
+            *replaced = donated.clone();
+        }
+        if offspring.depth() > max_depth {
+            return self.clone();
+        }
+        offspring
+    }
+
+    /// Possibly replaces a random subtree with a new random one that keeps the tree within the
+    /// maximum depth, and possibly perturbs each of the tree's constants
+    pub fn mutate(&mut self, config: &TreeConfig, rng: &mut StdRng) {
+        if rng.gen_range(0.0, 1.0) < config.subtree_mutation_rate {
+            let index = rng.gen_range(0, self.size());
+            let depth_above = self.depth_of(index).unwrap_or(0);
+            let max_depth = config.max_depth.saturating_sub(depth_above).max(1);
+            if let Some(subtree) = self.subtree_mut(index) {
+                *subtree = Node::random(max_depth, rng);
+            }
+        }
+        self.mutate_constants(config, rng);
+    }
+
+    /// Perturbs each of the tree's constants with the constant mutation rate
+    fn mutate_constants(&mut self, config: &TreeConfig, rng: &mut StdRng) {
+        let perturbation = Normal::new(0.0, config.constant_sigma.abs()).unwrap();
+        let perturb = |value: &mut f32, rng: &mut StdRng| {
+            if rng.gen_range(0.0, 1.0) < config.constant_mutation_rate {
+                *value += perturbation.sample(rng);
+            }
+        };
+        match self {
+            Node::Wander { turn } => perturb(turn, rng),
+            Node::IfHealthBelow { threshold, .. } => {
+                perturb(threshold, rng);
+                *threshold = threshold.clamp(0.0, 1.0);
+            }
+            Node::IfPredatorWithin { radius, .. } => {
+                perturb(radius, rng);
+                *radius = radius.clamp(0.0, 1.0);
+            }
+            _ => (),
+        }
+        for child in self.children_mut() {
+            child.mutate_constants(config, rng);
+        }
+    }
+
+    /// Returns the number of nodes above the node at the provided index, where nodes are
+    /// numbered in pre-order
+    fn depth_of(&self, index: usize) -> Option<usize> {
+        if index == 0 {
+            return Some(0);
+        }
+        let mut index = index - 1;
+        for child in self.children() {
+            let size = child.size();
+            if index < size {
+                return child.depth_of(index).map(|depth| depth + 1);
+            }
+            index -= size;
+        }
+        None
+    }
+
+    /// Returns the number of nodes that differ between this tree and another when they are laid
+    /// over each other from the root, counting every node that only one of them has
+    pub fn difference(&self, other: &Node) -> usize {
+        let same_kind = std::mem::discriminant(self) == std::mem::discriminant(other);
+        let (children, other_children) = (self.children(), other.children());
+        let shared = children.len().min(other_children.len());
+        let unshared: usize = children[shared..]
+            .iter()
+            .chain(other_children[shared..].iter())
+            .map(|child| child.size())
+            .sum();
+        let differences: usize = children
+            .iter()
+            .zip(other_children.iter())
+            .map(|(child, other_child)| child.difference(other_child))
+            .sum();
+        usize::from(!same_kind) + unshared + differences
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Node::SeekFood => write!(f, "seek_food"),
+            Node::FleePredator => write!(f, "flee_predator"),
+            Node::Wander { turn } => write!(f, "wander({:.2})", turn),
+            Node::IfHealthBelow {
+                threshold,
+                then,
+                otherwise,
+            } => write!(
+                f,
+                "if_health_below({:.2}, {}, {})",
+                threshold, then, otherwise
+            ),
+            Node::IfPredatorWithin {
+                radius,
+                then,
+                otherwise,
+            } => write!(
+                f,
+                "if_predator_within({:.2}, {}, {})",
+                radius, then, otherwise
+            ),
+            Node::Both(a, b) => write!(f, "both({}, {})", a, b),
+        }
+    }
+}
+
+/// Testing that trees steer by their conditions and stay within the maximum depth as they evolve
+#[test]
+fn test_tree() {
+    use rand::SeedableRng;
+
+    let tree = Node::IfPredatorWithin {
+        radius: 0.5,
+        then: Box::new(Node::FleePredator),
+        otherwise: Box::new(Node::SeekFood),
+    };
+    let mut senses = Senses {
+        target: Some(Vector2::new(0.0, 2.0)),
+        predator: Some(Vector2::new(0.3, 0.0)),
+        velocity: Vector2::new(1.0, 0.0),
+        health: 1.0,
+    };
+    assert_eq!(tree.steer(&senses), Vector2::new(-1.0, 0.0));
+    senses.predator = None;
+    assert_eq!(tree.steer(&senses), Vector2::new(0.0, 1.0));
+    assert_eq!(tree.size(), 3);
+    assert_eq!(tree.depth(), 2);
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let config = TreeConfig {
+        subtree_mutation_rate: 1.0,
+        ..TreeConfig::default()
+    };
+    let mut offspring = tree.clone();
+    for _ in 0..50 {
+        let mate = Node::random(config.max_depth, &mut rng);
+        offspring = offspring.crossover(&mate, config.max_depth, &mut rng);
+        offspring.mutate(&config, &mut rng);
+        assert!(offspring.depth() <= config.max_depth, "{}", offspring);
+    }
+    assert_eq!(tree.difference(&tree), 0);
+}
@@ -0,0 +1,164 @@
+//! A module for the geometry of the space that the entities live in.
+
+use ggez::nalgebra::{Point2, Vector2};
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::arena::Arena;
+
+/// The distance between the points that are checked for walls along a line of sight
+const SIGHT_STEP: f32 = 2.0;
+
+/// The number of attempts made at finding a random position that isn't within a wall
+const SPAWN_ATTEMPTS: usize = 100;
+
+/// The ways that fish are kept within the world
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum BoundaryMode {
+    /// Fish within the boundary padding are steered back towards the center of the world
+    Steer,
+    /// Fish that leave one edge of the world reappear on the opposite edge, and distances are
+    /// measured across the edges, as if the world were a torus
+    Wrap,
+    /// Fish bounce off of hard walls at the boundary padding
+    Reflect,
+}
+
+/// The space that the entities live in
+pub struct World {
+    /// The (width, height) of the world
+    pub size: (f32, f32),
+    /// The thickness of the padding that bounds the fish within the world
+    pub boundary_padding: f32,
+    /// How fish are kept within the world
+    pub boundary_mode: BoundaryMode,
+    /// The static walls within the world
+    pub arena: Arena,
+}
+
+impl World {
+    /// Creates a new world with the provided dimensions and boundary
+    pub fn new(
+        size: (f32, f32),
+        boundary_padding: f32,
+        boundary_mode: BoundaryMode,
+        arena: Arena,
+    ) -> Self {
+        Self {
+            size,
+            boundary_padding,
+            boundary_mode,
+            arena,
+        }
+    }
+
+    /// Returns the center of the world
+    pub fn center(&self) -> Point2<f32> {
+        Point2::new(self.size.0 / 2.0, self.size.1 / 2.0)
+    }
+
+    /// Returns the shortest vector from one point to another.
+    /// In `BoundaryMode::Wrap`, this may cross the edges of the world.
+    pub fn offset(&self, from: Point2<f32>, to: Point2<f32>) -> Vector2<f32> {
+        let mut offset = to - from;
+        if self.boundary_mode == BoundaryMode::Wrap {
+            offset.x = wrap_offset(offset.x, self.size.0);
+            offset.y = wrap_offset(offset.y, self.size.1);
+        }
+        offset
+    }
+
+    /// Returns the shortest distance between two points.
+    /// In `BoundaryMode::Wrap`, this may cross the edges of the world.
+    pub fn distance(&self, a: Point2<f32>, b: Point2<f32>) -> f32 {
+        self.offset(a, b).magnitude()
+    }
+
+    /// Returns the provided point moved onto the opposite edge(s) of the world if it has left it
+    pub fn wrap(&self, pos: Point2<f32>) -> Point2<f32> {
+        Point2::new(pos.x.rem_euclid(self.size.0), pos.y.rem_euclid(self.size.1))
+    }
+
+    /// Returns whether or not the provided point is within a wall of the arena
+    pub fn is_wall(&self, pos: Point2<f32>) -> bool {
+        let pos = match self.boundary_mode {
+            BoundaryMode::Wrap => self.wrap(pos),
+            _ => pos,
+        };
+        self.arena.is_wall(pos, self.size)
+    }
+
+    /// Returns whether or not the line between two points is free of walls, if walls block sight
+    pub fn line_of_sight(&self, from: Point2<f32>, to: Point2<f32>) -> bool {
+        if !self.arena.walls_block_sight || !self.arena.has_walls() {
+            return true;
+        }
+        let offset = self.offset(from, to);
+        let steps = (offset.magnitude() / SIGHT_STEP).ceil() as usize;
+        (1..steps).all(|step| !self.is_wall(from + offset * (step as f32 / steps as f32)))
+    }
+
+    /// Returns a random position within the boundary padding that isn't within a wall, if one
+    /// could be found.
+    /// Nothing is returned if the boundary padding covers the whole world.
+    pub fn random_open_position(&self, rng: &mut StdRng) -> Option<Point2<f32>> {
+        if !leaves_open_area(self.size, self.boundary_padding) {
+            return None;
+        }
+        (0..SPAWN_ATTEMPTS)
+            .map(|_| {
+                Point2::new(
+                    rng.gen_range(self.boundary_padding, self.size.0 - self.boundary_padding),
+                    rng.gen_range(self.boundary_padding, self.size.1 - self.boundary_padding),
+                )
+            })
+            .find(|pos| !self.is_wall(*pos))
+    }
+}
+
+/// Returns whether or not a boundary padding leaves some of a world of the provided size open
+pub fn leaves_open_area(size: (f32, f32), boundary_padding: f32) -> bool {
+    2.0 * boundary_padding < size.0.min(size.1)
+}
+
+/// Returns the shortest signed offset along one axis of a wrapping world of the provided length
+fn wrap_offset(offset: f32, length: f32) -> f32 {
+    if offset > length / 2.0 {
+        offset - length
+    } else if offset < -length / 2.0 {
+        offset + length
+    } else {
+        offset
+    }
+}
+
+/// Testing that distances in a wrapping world are measured across the edges
+#[test]
+fn test_wrap_distance() {
+    let world = World::new((100.0, 50.0), 0.0, BoundaryMode::Wrap, Arena::default());
+    let distance = world.distance(Point2::new(5.0, 25.0), Point2::new(95.0, 25.0));
+    assert_eq!(distance, 10.0);
+    assert_eq!(world.wrap(Point2::new(-5.0, 55.0)), Point2::new(95.0, 5.0));
+
+    // The same points are further apart when the world doesn't wrap
+    let world = World::new((100.0, 50.0), 0.0, BoundaryMode::Steer, Arena::default());
+    let distance = world.distance(Point2::new(5.0, 25.0), Point2::new(95.0, 25.0));
+    assert_eq!(distance, 90.0);
+}
+
+/// Testing that no position is found once the boundary padding covers the whole world
+#[test]
+fn test_random_open_position() {
+    use rand::SeedableRng;
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut world = World::new((100.0, 50.0), 10.0, BoundaryMode::Steer, Arena::default());
+    let pos = world.random_open_position(&mut rng).unwrap();
+    assert!(pos.x >= 10.0 && pos.x <= 90.0 && pos.y >= 10.0 && pos.y <= 40.0);
+
+    world.boundary_padding = 25.0;
+    assert!(world.random_open_position(&mut rng).is_none());
+    world.size = (0.0, 0.0);
+    world.boundary_padding = 0.0;
+    assert!(world.random_open_position(&mut rng).is_none());
+}